@@ -55,8 +55,11 @@ fn parse_json(rawdata: &str) -> Option<Vec<Vec<Vec<f64>>>> {
                             let points = contour[j].as_array().unwrap();
                             let mut vp: Vec<f64> = Vec::new();
                             for k in 0..points.len() {
-                                let val = points[k].to_string();
-                                let pval = val.parse::<f64>().unwrap();
+                                // null stands for NaN, see earcutr::fixture_json
+                                let pval = match points[k].is_null() {
+                                    true => f64::NAN,
+                                    false => points[k].to_string().parse::<f64>().unwrap(),
+                                };
                                 vp.push(pval);
                             }
                             vc.push(vp);
@@ -471,6 +474,38 @@ fn bench_issue83(bench: &mut Bencher) {
     mkoutput(nm, triangles);
 }
 
+// generated polygons, see earcutr::generate
+fn bench_generated(bench: &mut Bencher, rings: Vec<Vec<Vec<f64>>>) {
+    let (data, holeidxs, dimensions) = earcutr::flatten(&rings);
+    bench.iter(|| {
+        earcutr::earcut(&data, &holeidxs, dimensions);
+    });
+}
+
+fn bench_generated_star_10k(bench: &mut Bencher) {
+    bench_generated(bench, earcutr::generate::star(1, 10000));
+}
+
+fn bench_generated_spiral_10k(bench: &mut Bencher) {
+    bench_generated(bench, earcutr::generate::spiral(1, 10000, 20.0));
+}
+
+fn bench_generated_comb_10k(bench: &mut Bencher) {
+    bench_generated(bench, earcutr::generate::comb(1, 2500));
+}
+
+fn bench_generated_hilbert_4k(bench: &mut Bencher) {
+    bench_generated(bench, earcutr::generate::hilbert(6));
+}
+
+fn bench_generated_holes_100(bench: &mut Bencher) {
+    bench_generated(bench, earcutr::generate::holes(1, 100, 20));
+}
+
+fn bench_generated_sliver_10k(bench: &mut Bencher) {
+    bench_generated(bench, earcutr::generate::sliver(1, 10000, 1.0));
+}
+
 benchmark_group!(
     benches,
     bench_indices_3d,
@@ -510,5 +545,11 @@ benchmark_group!(
     bench_water3,
     bench_water3b,
     bench_water4,
+    bench_generated_star_10k,
+    bench_generated_spiral_10k,
+    bench_generated_comb_10k,
+    bench_generated_hilbert_4k,
+    bench_generated_holes_100,
+    bench_generated_sliver_10k,
 );
 benchmark_main!(benches);
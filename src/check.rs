@@ -0,0 +1,153 @@
+// integrity checks of the linked lists, run after every change to them
+// when built with the 'check' feature. a broken link panics right where
+// it happened, with a dump of the ring, instead of turning up much later
+// as an endless loop or as missing triangles
+
+use super::{LinkedLists, NodeIdx, NULL};
+
+// check the ring that holds node 'start', and the z-order list through
+// node 'z', after the change named by 'what'
+pub(crate) fn check_links(ll: &LinkedLists, what: &str, start: NodeIdx, z: NodeIdx) {
+    if let Err(e) = check_ring(ll, start).and_then(|_| check_zorder(ll, z)) {
+        panic!("{} broke the linked list: {}\n{}", what, e, ring_dump(ll, start));
+    }
+}
+
+// prev and next are inverses of each other all around the ring, and the
+// ring doesn't reach the NULL node or a removed node
+fn check_ring(ll: &LinkedLists, start: NodeIdx) -> Result<(), String> {
+    let mut p = start;
+    for _ in 0..ll.nodes.len() {
+        let n = ll.nodes[p].next_idx;
+        if p == NULL {
+            return Err("the ring reaches the NULL node".to_string());
+        }
+        if ll.removed(p) {
+            return Err(format!("the ring reaches removed node {}", p));
+        }
+        if ll.nodes[n].prev_idx != p {
+            let np = ll.nodes[n].prev_idx;
+            return Err(format!("next of {} is {}, but prev of {} is {}", p, n, n, np));
+        }
+        p = n;
+        if p == start {
+            return Ok(());
+        }
+    }
+    Err(format!("the ring doesn't get back to {}", start))
+}
+
+// prevz and nextz are inverses of each other, z never decreases along
+// nextz, and no removed node is in the list. nodes that were never
+// indexed have no z-order links, and pass trivially
+fn check_zorder(ll: &LinkedLists, z: NodeIdx) -> Result<(), String> {
+    let mut p = z;
+    let mut count = 0;
+    while p != NULL && ll.nodes[p].prevz_idx != NULL {
+        p = ll.nodes[p].prevz_idx;
+        count += 1;
+        if count > ll.nodes.len() {
+            return Err(format!("the z-order list through {} has a cycle", z));
+        }
+    }
+    let mut count = 0;
+    while p != NULL {
+        let n = ll.nodes[p].nextz_idx;
+        if ll.removed(p) {
+            return Err(format!("the z-order list reaches removed node {}", p));
+        }
+        if n != NULL && ll.nodes[n].prevz_idx != p {
+            let np = ll.nodes[n].prevz_idx;
+            return Err(format!("nextz of {} is {}, but prevz of {} is {}", p, n, n, np));
+        }
+        if n != NULL && ll.nodes[n].z < ll.nodes[p].z {
+            return Err(format!("z-order list not sorted: z of {} > z of {}", p, n));
+        }
+        p = n;
+        count += 1;
+        if count > ll.nodes.len() {
+            return Err(format!("the z-order list through {} has a cycle", z));
+        }
+    }
+    Ok(())
+}
+
+impl LinkedLists {
+    // whether remove_node took node p out of its ring
+    pub(crate) fn removed(&self, p: NodeIdx) -> bool {
+        self.nodes[p].removed
+    }
+}
+
+// the nodes along the ring from 'start', one per line, up to the first
+// node seen twice. links that don't match are pointed out
+fn ring_dump(ll: &LinkedLists, start: NodeIdx) -> String {
+    let mut s = format!(
+        " {:>5} {:>5} {:>5} {:>5} {:>10} {:>10} {:>5} {:>5} {:>6}\n",
+        "idx", "i", "prev", "next", "x", "y", "prevz", "nextz", "z"
+    );
+    let mut seen = vec![false; ll.nodes.len()];
+    let mut p = start;
+    while !seen[p] {
+        seen[p] = true;
+        let n = &ll.nodes[p];
+        s.push_str(&format!(
+            " {:>5} {:>5} {:>5} {:>5} {:>10.3} {:>10.3} {:>5} {:>5} {:>6}",
+            n.idx, n.i, n.prev_idx, n.next_idx, n.x, n.y, n.prevz_idx, n.nextz_idx, n.z
+        ));
+        if ll.removed(p) {
+            s.push_str(" removed");
+        }
+        if ll.nodes[n.next_idx].prev_idx != p {
+            s.push_str(&format!(" <- prev of next is {}", ll.nodes[n.next_idx].prev_idx));
+        }
+        s.push('\n');
+        p = n.next_idx;
+    }
+    s.push_str(&format!(" back to {}\n", p));
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{index_curve, linked_list, split_bridge_polygon};
+    use super::*;
+
+    #[test]
+    fn test_check_ring() {
+        let data = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+        let (mut ll, start) = linked_list(&data, 0, data.len(), true);
+        assert!(check_ring(&ll, start).is_ok());
+        ll.remove_node(2);
+        assert!(check_ring(&ll, start).is_ok());
+        // a stale link to the node that was removed
+        ll.nodes[3].prev_idx = 2;
+        ll.nodes[1].next_idx = 2;
+        assert!(check_ring(&ll, 1).unwrap_err().contains("removed node 2"));
+        ll.nodes[1].next_idx = 3;
+        assert!(check_ring(&ll, 1).unwrap_err().contains("prev of 3 is 2"));
+        assert!(check_ring(&ll, NULL).is_err());
+        assert!(ring_dump(&ll, 1).contains("<- prev of next is 2"));
+    }
+
+    #[test]
+    fn test_check_zorder() {
+        let data = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+        let (mut ll, start) = linked_list(&data, 0, data.len(), true);
+        ll.invsize = 1.0;
+        index_curve(&mut ll, start);
+        assert!(check_zorder(&ll, start).is_ok());
+        let (a, b) = (ll.nodes[start].nextz_idx, ll.nodes[start].prevz_idx);
+        let z = if a != NULL { a } else { b };
+        ll.nodes[z].z = if a != NULL { -1 } else { i32::MAX };
+        assert!(check_zorder(&ll, start).unwrap_err().contains("not sorted"));
+    }
+
+    #[test]
+    #[should_panic(expected = "split_bridge_polygon broke the linked list")]
+    fn test_check_links() {
+        let data = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+        let (mut ll, _) = linked_list(&data, 0, data.len(), true);
+        split_bridge_polygon(&mut ll, NULL, 1);
+    }
+}
@@ -0,0 +1,224 @@
+// constraints: polylines inside the polygon, like zoning lines or road
+// centerlines, whose segments must be edges of the triangles, so that no
+// triangle is on both sides of one. each polyline is added as a hole of
+// no area that doubles back on itself, p0 .. pk .. p1. it is bridged to
+// the outer ring by split_bridge_polygon like any other hole, after which
+// its segments are ring edges, and so triangle edges. its nodes are
+// steiner points, which keeps filter_points from taking out the tips,
+// where the ring turns back. a polyline that ends on a ring is joined to
+// it there instead, by a bridge of no length, splitting the ring edge if
+// the end is not at a vertex. one that ends on rings at both ends cuts
+// the polygon in two
+
+use super::steiner::{locate, point, renumber, rings, Location, Rejected, Triangles};
+use super::verify::{crossing, on_segment, Point};
+use super::{check_hole_indices, earcut_ll, earcut_with_options, Coord, EarcutOptions, Join, LinkedLists, DIM};
+
+// same as earcut_with_options(), plus constraint polylines, each
+// flattened like data; a coordinate left over at the end of one is
+// ignored, as it is in data. a polyline of one point is a steiner point.
+// the triangles number the points of the polylines after the n vertices
+// of the polygon, in order: point j of the first polyline is vertex n + j,
+// the points of the second come next, and so on. polylines that aren't
+// inside the polygon, that cross or touch themselves, a ring or an
+// earlier polyline, or that repeat a point, are in no triangle, and are
+// returned by their index, with the reason. the ends of a polyline of two
+// points or more may be on a ring: on an edge, or at a vertex, whose
+// number it then takes instead of its own. on an error about the
+// polygon itself no polyline is checked, and none is rejected
+pub fn earcut_with_constraints<T: Coord>(
+    data: &Vec<T>,
+    hole_indices: &Vec<usize>,
+    dims: usize,
+    polylines: &[Vec<T>],
+    options: &EarcutOptions,
+) -> (Triangles, Vec<(usize, Rejected)>) {
+    let n = data.len() / DIM;
+    if dims != DIM || check_hole_indices(hole_indices, n).is_err() {
+        return (earcut_with_options(data, hole_indices, dims, options), Vec::new());
+    }
+    let points: Vec<Point> = data[..n * DIM].chunks(DIM).map(point).collect();
+    let rings = rings(&points, hole_indices);
+    let edges: Vec<[Point; 2]> = rings
+        .iter()
+        .flat_map(|ring| (0..ring.len()).map(move |i| [ring[i], ring[(i + 1) % ring.len()]]))
+        .collect();
+
+    let mut all = data[..n * DIM].to_vec();
+    let mut holes = hole_indices.clone();
+    // the vertex number of each vertex of 'all' past the polygon's
+    let mut vertex: Vec<usize> = Vec::new();
+    // the points and segments of the polylines added so far
+    let mut added: Vec<Point> = Vec::new();
+    let mut segments: Vec<[Point; 2]> = Vec::new();
+    let mut joins = Vec::new();
+    let mut rejected = Vec::new();
+    let mut first = n;
+    for (c, line) in polylines.iter().enumerate() {
+        let line = &line[..line.len() - line.len() % DIM];
+        let k = line.len() / DIM;
+        let points: Vec<Point> = line.chunks(DIM).map(point).collect();
+        match check_polyline(&rings, &edges, &added, &segments, &points) {
+            Some(why) => rejected.push((c, why)),
+            None if k == 0 => {}
+            None => {
+                let start = all.len() / DIM;
+                holes.push(start);
+                // the ends on a ring, with the point next to each
+                let ends = match k {
+                    1 => vec![],
+                    _ => vec![(0, 1), (k - 1, k - 2)],
+                };
+                for (j, from) in ends {
+                    if let Some((a, b)) = on_ring(&rings, hole_indices, points[j]) {
+                        let split = joins.last().is_some_and(|l: &Join| l.end == start);
+                        joins.push(Join { end: start + j, from: start + from, a, b, split });
+                    }
+                }
+                for j in (0..k).chain((1..k - 1).rev()) {
+                    all.extend_from_slice(&line[j * DIM..j * DIM + DIM]);
+                    vertex.push(first + j);
+                }
+                // an end at a ring vertex is that vertex
+                for join in joins.iter().filter(|j| j.end >= start && j.a == j.b) {
+                    vertex[join.end - n] = join.a;
+                }
+                segments.extend(points.windows(2).map(|s| [s[0], s[1]]));
+                added.extend(points);
+            }
+        }
+        first += k;
+    }
+
+    let mut ll = LinkedLists::new(all.len() / DIM);
+    ll.constraints_from = n;
+    ll.joins = joins;
+    let result = earcut_ll(&mut ll, &all, &holes, dims, options);
+    let result = renumber(result, |i| match i < n {
+        true => i,
+        false => vertex[i - n],
+    });
+    (result, rejected)
+}
+
+// why the polyline of 'points' can't be added to the polygon of 'rings'
+// and 'edges', with the 'added' points and 'segments' of earlier
+// polylines, if it can't
+fn check_polyline(
+    rings: &[&[Point]],
+    edges: &[[Point; 2]],
+    added: &[Point],
+    segments: &[[Point; 2]],
+    points: &[Point],
+) -> Option<Rejected> {
+    // the ends that are on a ring
+    let mut on_ring = Vec::new();
+    for (j, &p) in points.iter().enumerate() {
+        let end = points.len() > 1 && (j == 0 || j == points.len() - 1);
+        match locate(rings, p) {
+            Location::Outside => return Some(Rejected::Outside),
+            Location::OnBoundary if end => on_ring.push(p),
+            Location::OnBoundary => return Some(Rejected::OnBoundary),
+            Location::Inside => {}
+        }
+        if points[..j].contains(&p) || added.contains(&p) {
+            return Some(Rejected::Duplicate);
+        }
+    }
+    let own: Vec<[Point; 2]> = points.windows(2).map(|s| [s[0], s[1]]).collect();
+    for (j, s) in own.iter().enumerate() {
+        if edges.iter().any(|t| touch(s, t, &on_ring))
+            || segments.iter().chain(&own[..j.saturating_sub(1)]).any(|t| touch(s, t, &[]))
+        {
+            return Some(Rejected::Crosses);
+        }
+        // the segment before shares an end with this one, and must not
+        // run back along it
+        if j > 0 && (on_segment(s[0], s[1], own[j - 1][0]) || on_segment(own[j - 1][0], s[0], s[1])) {
+            return Some(Rejected::Crosses);
+        }
+    }
+    // a segment from ring to ring that touches no edge on the way is
+    // inside or outside all along
+    if on_ring.len() == 2 && points.len() == 2 {
+        let middle = [(points[0][0] + points[1][0]) / 2.0, (points[0][1] + points[1][1]) / 2.0];
+        match locate(rings, middle) {
+            Location::Inside => {}
+            _ => return Some(Rejected::Outside),
+        }
+    }
+    None
+}
+
+// whether segments s and t have any point in common, but for the points
+// of 'except'
+fn touch(s: &[Point; 2], t: &[Point; 2], except: &[Point]) -> bool {
+    let at = |p: &[Point; 2], r: Point| on_segment(p[0], p[1], r) && !except.contains(&r);
+    crossing(s[0], s[1], t[0], t[1]).is_some() || at(s, t[0]) || at(s, t[1]) || at(t, s[0]) || at(t, s[1])
+}
+
+// the vertices of the polygon at the ends of the ring edge p is on, or
+// the vertex at p, twice. None if p isn't on a ring
+fn on_ring(rings: &[&[Point]], hole_indices: &[usize], p: Point) -> Option<(usize, usize)> {
+    let first = |r: usize| match r {
+        0 => 0,
+        _ => hole_indices[r - 1],
+    };
+    let vertex = rings.iter().enumerate().find_map(|(r, ring)| {
+        ring.iter().position(|&q| q == p).map(|i| (first(r) + i, first(r) + i))
+    });
+    vertex.or_else(|| {
+        rings.iter().enumerate().find_map(|(r, ring)| {
+            let next = |i: usize| (i + 1) % ring.len();
+            (0..ring.len())
+                .find(|&i| on_segment(ring[i], ring[next(i)], p))
+                .map(|i| (first(r) + i, first(r) + next(i)))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_polyline() {
+        let outer = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]];
+        let hole = [[6.0, 6.0], [6.0, 8.0], [8.0, 8.0], [8.0, 6.0]];
+        let rings: Vec<&[Point]> = vec![&outer, &hole];
+        let edges: Vec<[Point; 2]> = (0..4)
+            .map(|i| [outer[i], outer[(i + 1) % 4]])
+            .chain((0..4).map(|i| [hole[i], hole[(i + 1) % 4]]))
+            .collect();
+        let check = |points: &[Point]| check_polyline(&rings, &edges, &[[1.0, 1.0]], &[[[1.0, 1.0], [1.0, 3.0]]], points);
+        assert!(check(&[[2.0, 1.0], [4.0, 2.0], [2.0, 5.0]]).is_none());
+        assert!(check(&[[2.0, 1.0]]).is_none());
+        assert!(check(&[]).is_none());
+        assert!(check(&[[2.0, 1.0], [11.0, 2.0]]) == Some(Rejected::Outside));
+        assert!(check(&[[2.0, 1.0], [7.0, 7.0]]) == Some(Rejected::Outside));
+        assert!(check(&[[2.0, 1.0], [6.0, 7.0], [5.0, 8.0]]) == Some(Rejected::OnBoundary));
+        assert!(check(&[[6.0, 7.0]]) == Some(Rejected::OnBoundary));
+        // ends on a ring: at a corner, on an edge, and across the square
+        assert!(check(&[[0.0, 0.0], [2.0, 1.0], [6.0, 7.0]]).is_none());
+        assert!(check(&[[0.0, 5.0], [5.0, 5.0], [10.0, 5.0]]).is_none());
+        assert!(check(&[[0.0, 5.0], [10.0, 5.0]]).is_none());
+        assert!(check(&[[6.0, 6.0], [0.0, 10.0]]).is_none());
+        // but not into or across the hole, or along a ring
+        assert!(check(&[[0.0, 7.0], [10.0, 7.0]]) == Some(Rejected::Crosses));
+        assert!(check(&[[6.0, 6.0], [8.0, 8.0]]) == Some(Rejected::Outside));
+        assert!(check(&[[0.0, 0.0], [10.0, 0.0]]) == Some(Rejected::Outside));
+        assert!(check(&[[5.0, 10.0], [7.0, 5.0]]) == Some(Rejected::Crosses));
+        assert!(check(&[[2.0, 1.0], [2.0, 4.0], [2.0, 1.0]]) == Some(Rejected::Duplicate));
+        assert!(check(&[[1.0, 1.0], [2.0, 4.0]]) == Some(Rejected::Duplicate));
+        // through the hole, and through a corner of it
+        assert!(check(&[[5.0, 7.0], [9.0, 7.0]]) == Some(Rejected::Crosses));
+        assert!(check(&[[5.0, 5.0], [9.0, 9.0]]) == Some(Rejected::Crosses));
+        // across the earlier polyline, and ending on it
+        assert!(check(&[[0.5, 2.0], [2.0, 2.0]]) == Some(Rejected::Crosses));
+        assert!(check(&[[1.0, 2.0], [2.0, 2.0]]) == Some(Rejected::Crosses));
+        // across itself, and back along itself
+        assert!(check(&[[2.0, 1.0], [4.0, 3.0], [4.0, 1.0], [2.0, 3.0]]) == Some(Rejected::Crosses));
+        assert!(check(&[[2.0, 1.0], [4.0, 1.0], [3.0, 1.0]]) == Some(Rejected::Crosses));
+        assert!(check(&[[3.0, 1.0], [4.0, 1.0], [2.0, 1.0]]) == Some(Rejected::Crosses));
+    }
+}
@@ -0,0 +1,173 @@
+// the delaunay pass, see EarcutOptions::delaunay. ear clipping takes the
+// first ear it finds, which leaves long thin triangles. lawson's flips
+// fix that: two triangles sharing a diagonal make a quadrilateral, and if
+// the far corner of one is inside the circumcircle of the other, the
+// diagonal is swapped for the other one. repeated until no diagonal needs
+// it, the triangles are constrained delaunay, the ring edges being the
+// constraints. a flip keeps the vertices and the number of triangles, and
+// covers the same quadrilateral, so the polygon stays covered
+//
+// each flip lowers the triangles, lifted onto the paraboloid z = x² + y²,
+// so the flips end, as long as every one is right. a flip is only made
+// when the in-circle test is sure, see incircle; nearly cocircular
+// corners are left as they are
+
+use std::collections::{HashMap, HashSet};
+
+use super::robust::orient2d;
+use super::steiner::point;
+use super::verify::Point;
+use super::{Coord, DIM};
+
+// flip the diagonals of 'triangles', made by earcut from 'data' and
+// 'hole_indices', until they are constrained delaunay. edges of the rings
+// aren't flipped, nor are edges that aren't between exactly two
+// triangles, one each way. returns the number of flips
+pub(crate) fn flip<T: Coord>(data: &[T], hole_indices: &[usize], triangles: &mut [usize]) -> usize {
+    let points: Vec<Point> = data.chunks_exact(DIM).map(point).collect();
+    let mut bounds = vec![0];
+    bounds.extend(hole_indices.iter().cloned());
+    bounds.push(points.len());
+    let fixed: HashSet<(usize, usize)> = bounds
+        .windows(2)
+        .filter(|b| b[0] < b[1])
+        .flat_map(|b| (b[0]..b[1]).map(move |i| edge(i, if i + 1 < b[1] { i + 1 } else { b[0] })))
+        .collect();
+
+    // the triangle of each edge, by its direction. an edge that a bad
+    // triangulation has twice the same way is left alone
+    let mut sides: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut twice: HashSet<(usize, usize)> = HashSet::new();
+    for t in 0..triangles.len() / 3 {
+        for (a, b) in corners(triangles, t) {
+            if sides.insert((a, b), t).is_some() {
+                twice.insert(edge(a, b));
+            }
+        }
+    }
+
+    // in the order of the triangles, not of the hash map, so the same
+    // triangles always flip the same way
+    let mut todo: Vec<(usize, usize)> = (0..triangles.len() / 3)
+        .flat_map(|t| corners(triangles, t))
+        .filter(|&(a, b)| a < b)
+        .collect();
+    let mut flips = 0;
+    while let Some((a, b)) = todo.pop() {
+        if fixed.contains(&(a, b)) || twice.contains(&(a, b)) {
+            continue;
+        }
+        let (t, u) = match (sides.get(&(a, b)), sides.get(&(b, a))) {
+            (Some(&t), Some(&u)) => (t, u),
+            _ => continue,
+        };
+        let c = third(triangles, t, a, b);
+        let d = third(triangles, u, a, b);
+        if c == d || sides.contains_key(&(c, d)) || sides.contains_key(&(d, c)) {
+            continue;
+        }
+        let [pa, pb, pc, pd] = [points[a], points[b], points[c], points[d]];
+        // t goes a b c, u goes b a d. the quadrilateral a d b c must be
+        // strictly convex, both new triangles wound the way t is
+        let s = orient(pa, pb, pc).signum();
+        if s == 0.0
+            || orient(pb, pa, pd).signum() != s
+            || orient(pc, pa, pd).signum() != s
+            || orient(pd, pb, pc).signum() != s
+            || s * incircle(pa, pb, pc, pd) <= 0.0
+        {
+            continue;
+        }
+
+        for (x, y) in corners(triangles, t).chain(corners(triangles, u)) {
+            sides.remove(&(x, y));
+        }
+        triangles[3 * t..3 * t + 3].copy_from_slice(&[c, a, d]);
+        triangles[3 * u..3 * u + 3].copy_from_slice(&[d, b, c]);
+        for v in [t, u] {
+            for (x, y) in corners(triangles, v) {
+                sides.insert((x, y), v);
+            }
+        }
+        todo.extend([edge(a, c), edge(c, b), edge(b, d), edge(d, a)]);
+        flips += 1;
+    }
+    flips
+}
+
+// an undirected edge
+fn edge(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+// the edges of triangle t, in its direction
+fn corners(triangles: &[usize], t: usize) -> impl Iterator<Item = (usize, usize)> {
+    let v = [triangles[3 * t], triangles[3 * t + 1], triangles[3 * t + 2]];
+    (0..3).map(move |i| (v[i], v[(i + 1) % 3]))
+}
+
+// the corner of triangle t that isn't a or b
+fn third(triangles: &[usize], t: usize, a: usize, b: usize) -> usize {
+    let v = &triangles[3 * t..3 * t + 3];
+    v.iter().cloned().find(|&i| i != a && i != b).unwrap_or(v[0])
+}
+
+fn orient(a: Point, b: Point, c: Point) -> f64 {
+    orient2d(a[0], a[1], b[0], b[1], c[0], c[1])
+}
+
+// positive if d is inside the circle through a, b and c, when they wind
+// counterclockwise, negative if outside. 0 when the sign isn't certain:
+// the value is only returned if it is larger than the bound on its
+// rounding error, Shewchuk's iccerrboundA. infinity or NaN, from
+// coordinates too big to square, also give 0
+pub(crate) fn incircle(a: Point, b: Point, c: Point, d: Point) -> f64 {
+    let (adx, ady) = (a[0] - d[0], a[1] - d[1]);
+    let (bdx, bdy) = (b[0] - d[0], b[1] - d[1]);
+    let (cdx, cdy) = (c[0] - d[0], c[1] - d[1]);
+    let alift = adx * adx + ady * ady;
+    let blift = bdx * bdx + bdy * bdy;
+    let clift = cdx * cdx + cdy * cdy;
+    let det = alift * (bdx * cdy - cdx * bdy) + blift * (cdx * ady - adx * cdy) + clift * (adx * bdy - bdx * ady);
+    let permanent = ((bdx * cdy).abs() + (cdx * bdy).abs()) * alift
+        + ((cdx * ady).abs() + (adx * cdy).abs()) * blift
+        + ((adx * bdy).abs() + (bdx * ady).abs()) * clift;
+    let bound = (10.0 + 96.0 * f64::EPSILON / 2.0) * (f64::EPSILON / 2.0) * permanent;
+    match det.abs() > bound && det.is_finite() {
+        true => det,
+        false => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_incircle() {
+        let (a, b, c) = ([0.0, 0.0], [2.0, 0.0], [0.0, 2.0]);
+        assert!(incircle(a, b, c, [1.0, 1.0]) > 0.0);
+        assert!(incircle(a, b, c, [3.0, 3.0]) < 0.0);
+        assert!(incircle(c, b, a, [1.0, 1.0]) < 0.0);
+        // on the circle, and too close to it to tell
+        assert!(incircle(a, b, c, [2.0, 2.0]) == 0.0);
+        assert!(incircle(a, b, c, [2.0, 2.0000000000000004]) == 0.0);
+        assert!(incircle(a, b, c, [f64::MAX, 1.0]) == 0.0);
+    }
+
+    #[test]
+    fn test_flip() {
+        // a flat quadrilateral, cut along its long diagonal
+        let data = vec![0.0, 0.0, 5.0, -1.0, 10.0, 0.0, 5.0, 1.0];
+        let mut triangles = vec![0, 1, 2, 2, 3, 0];
+        assert!(flip(&data, &[], &mut triangles) == 1);
+        assert!(triangles == vec![1, 2, 3, 3, 0, 1]);
+        assert!(flip(&data, &[], &mut triangles) == 0);
+        // the same, where the long diagonal is an edge of the ring, and
+        // where it isn't as every vertex is a ring of its own
+        let data = vec![0.0, 0.0, 10.0, 0.0, 5.0, 1.0, 5.0, -1.0];
+        let mut triangles = vec![0, 3, 1, 1, 2, 0];
+        assert!(flip(&data, &[], &mut triangles) == 0);
+        assert!(flip(&data, &[1, 2, 3], &mut triangles) == 1);
+    }
+}
@@ -0,0 +1,128 @@
+// which input vertices and rings earcut left out of the triangles, and
+// why, see earcut_with_diagnostics. while earcut runs, every node that is
+// taken out of its ring, or given up on, is noted with a reason; at the
+// end the notes for vertices that made it into a triangle anyway, through
+// another node or before being dropped, are thrown away
+
+use super::{LinkedLists, NodeIdx, VertIdx};
+
+// why a vertex, or a whole ring, isn't in any triangle
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dropped {
+    // at the same place as the next vertex of its ring
+    Duplicate,
+    // on a straight line between its neighbors, or on a spike
+    Collinear,
+    // in a ring with nothing left to triangulate: too few vertices, or
+    // no area
+    DegenerateRing,
+    // in a hole that couldn't be bridged to the outer ring
+    NotBridged,
+    // cut off by cure_local_intersections, where the ring crosses itself
+    SelfIntersection,
+    // left over when no more ears could be found
+    Unclipped,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diagnostics {
+    // input vertices that are in no triangle, in vertex order
+    pub dropped: Vec<(VertIdx, Dropped)>,
+    // rings none of whose vertices are in a triangle. ring 0 is the
+    // outer ring, ring 1 the first hole, and so on
+    pub discarded_rings: Vec<(usize, Dropped)>,
+    // vertices of holes of one vertex, which are treated as steiner points
+    pub steiner: Vec<VertIdx>,
+}
+
+impl LinkedLists {
+    // note why node p is dropped, if diagnostics were asked for
+    pub(crate) fn note_dropped(&mut self, p: NodeIdx, why: Dropped) {
+        if let Some(d) = self.diagnostics.as_mut() {
+            d.dropped.push((self.nodes[p].i, why));
+        }
+    }
+
+    // the same for every node of the ring at 'start'. a ring of less than
+    // three nodes is degenerate, rather than unclipped
+    pub(crate) fn note_ring_dropped(&mut self, start: NodeIdx, why: Dropped) {
+        if self.diagnostics.is_some() {
+            let ring = super::ring_indices(self, start);
+            let why = match why {
+                Dropped::Unclipped if ring.len() < 3 => Dropped::DegenerateRing,
+                _ => why,
+            };
+            for i in ring {
+                if let Some(d) = self.diagnostics.as_mut() {
+                    d.dropped.push((i, why));
+                }
+            }
+        }
+    }
+}
+
+// turn the notes taken while triangulating n vertices into the report.
+// the last note about a vertex wins; a vertex no note was taken about was
+// never linked in, because its ring, or the whole input, was too small
+pub(crate) fn finish(
+    mut d: Diagnostics,
+    n: usize,
+    hole_indices: &[usize],
+    triangles: &[usize],
+) -> Diagnostics {
+    let mut used = vec![false; n];
+    for &i in triangles.iter().filter(|&&i| i < n) {
+        used[i] = true;
+    }
+    let mut why = vec![Dropped::DegenerateRing; n];
+    for &(i, w) in d.dropped.iter().filter(|&&(i, _)| i < n) {
+        why[i] = w;
+    }
+    d.dropped = (0..n).filter(|&i| !used[i]).map(|i| (i, why[i])).collect();
+
+    let mut bounds = vec![0];
+    bounds.extend(hole_indices.iter().map(|&h| h.min(n)));
+    bounds.push(n);
+    d.discarded_rings = Vec::new();
+    for r in 0..bounds.len() - 1 {
+        let ring = bounds[r]..bounds[r + 1].max(bounds[r]);
+        if ring.clone().any(|i| used[i]) {
+            continue;
+        }
+        let reasons: Vec<Dropped> = ring.map(|i| why[i]).collect();
+        let reason = if reasons.contains(&Dropped::NotBridged) {
+            Dropped::NotBridged
+        } else if reasons.contains(&Dropped::Unclipped) {
+            Dropped::Unclipped
+        } else {
+            Dropped::DegenerateRing
+        };
+        d.discarded_rings.push((r, reason));
+    }
+    d.steiner.sort_unstable();
+    d
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finish() {
+        let notes = Diagnostics {
+            dropped: vec![(1, Dropped::Collinear), (2, Dropped::Duplicate), (5, Dropped::NotBridged)],
+            discarded_rings: vec![],
+            steiner: vec![],
+        };
+        // vertex 2 made it into a triangle before it was dropped
+        let d = finish(notes, 8, &[4, 7], &[0, 2, 3]);
+        assert!(d.dropped == vec![
+            (1, Dropped::Collinear),
+            (4, Dropped::DegenerateRing),
+            (5, Dropped::NotBridged),
+            (6, Dropped::DegenerateRing),
+            (7, Dropped::DegenerateRing),
+        ]);
+        assert!(d.discarded_rings == vec![(1, Dropped::NotBridged), (2, Dropped::DegenerateRing)]);
+    }
+}
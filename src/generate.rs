@@ -0,0 +1,257 @@
+// synthetic polygons for tests and benchmarks, of any size. every
+// generator is deterministic: the same seed and size give the same
+// polygon. polygons come as rings of points, the outer ring first and
+// then the holes, like the files in tests/fixtures; flatten() turns them
+// into earcut's input
+
+use std::f64::consts::PI;
+
+type Rings = Vec<Vec<Vec<f64>>>;
+
+// a small seeded random number generator (splitmix64), so that the
+// polygons don't depend on a random crate, or on its version
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    // uniform in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+    // uniform in [lo, hi)
+    pub fn range(&mut self, lo: f64, hi: f64) -> f64 {
+        lo + (hi - lo) * self.next_f64()
+    }
+    // uniform in 0..n, n > 0
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+// a star-shaped polygon of n >= 3 vertices around 0,0: one vertex in
+// each of n equal sectors, at a random radius between 200 and 1000
+pub fn star(seed: u64, n: usize) -> Rings {
+    let mut rng = Rng::new(seed);
+    vec![star_ring(&mut rng, n, [0.0, 0.0], 200.0, 1000.0)]
+}
+
+// a thick spiral arm of 'turns' turns, with n/2 vertices along each side.
+// the arm is 50 wide, with 50 between the turns
+pub fn spiral(seed: u64, n: usize, turns: f64) -> Rings {
+    let mut rng = Rng::new(seed);
+    let side = (n / 2).max(2);
+    let radius = |t: f64| 100.0 + 100.0 * t / (2.0 * PI);
+    let mut outer = Vec::with_capacity(side);
+    let mut inner = Vec::with_capacity(side);
+    for i in 0..side {
+        let t = turns * 2.0 * PI * i as f64 / (side - 1) as f64;
+        let r = radius(t) + rng.range(-5.0, 5.0);
+        outer.push(vec![r * t.cos(), r * t.sin()]);
+        let r = radius(t) - 50.0 + rng.range(-5.0, 5.0);
+        inner.push(vec![r * t.cos(), r * t.sin()]);
+    }
+    inner.reverse();
+    outer.extend(inner);
+    vec![outer]
+}
+
+// a comb: a bar 10 high along the x axis, with 'teeth' teeth 10 wide and
+// between 20 and 1000 long sticking up from it. 4 * teeth + 2 vertices,
+// with at least one tooth
+pub fn comb(seed: u64, teeth: usize) -> Rings {
+    let mut rng = Rng::new(seed);
+    let teeth = teeth.max(1);
+    let w = 20.0 * teeth as f64;
+    let mut ring = vec![vec![0.0, 0.0], vec![w, 0.0], vec![w, 10.0]];
+    for i in (0..teeth).rev() {
+        let (x, h) = (20.0 * i as f64, rng.range(20.0, 1000.0).round());
+        ring.push(vec![x + 10.0, 10.0]);
+        ring.push(vec![x + 10.0, h]);
+        ring.push(vec![x, h]);
+        ring.push(vec![x, 10.0]);
+    }
+    // the first tooth is flush with the end of the bar
+    ring.pop();
+    vec![ring]
+}
+
+// the Hilbert curve of the given order through the 2^order by 2^order
+// grid, closed below the grid into a polygon of 4^order + 2 vertices,
+// like tests/fixtures/hilbert.json. order is 1 to 15: order 0 is a single
+// point, which the closing points come back to, and 4^16 points would
+// not fit in memory anyway
+pub fn hilbert(order: u32) -> Rings {
+    assert!((1..=15).contains(&order), "hilbert order {} is not in 1..=15", order);
+    let side = 1usize << order;
+    let mut ring: Vec<Vec<f64>> = (0..side * side)
+        .map(|d| {
+            let (x, y) = hilbert_point(side, d);
+            vec![x as f64, y as f64]
+        })
+        .collect();
+    ring.push(vec![(side - 1) as f64, -1.0]);
+    ring.push(vec![0.0, -1.0]);
+    vec![ring]
+}
+
+// the point at distance d along the Hilbert curve through a side by side
+// grid, from 0,0 to side-1,0
+fn hilbert_point(side: usize, d: usize) -> (usize, usize) {
+    let (mut x, mut y, mut t) = (0, 0, d);
+    let mut s = 1;
+    while s < side {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+    (x, y)
+}
+
+// a 1000 by 1000 square with 'holes' star-shaped holes of 'hole_vertices'
+// vertices each. every hole has a cell of a grid over the square to
+// itself, so no two holes touch
+pub fn holes(seed: u64, holes: usize, hole_vertices: usize) -> Rings {
+    let mut rng = Rng::new(seed);
+    let mut rings = vec![vec![vec![0.0, 0.0], vec![1000.0, 0.0], vec![1000.0, 1000.0], vec![0.0, 1000.0]]];
+    let grid = (holes as f64).sqrt().ceil() as usize;
+    let cell = 1000.0 / grid.max(1) as f64;
+    let mut cells: Vec<usize> = (0..grid * grid).collect();
+    for i in 0..holes {
+        let j = i + rng.below(cells.len() - i);
+        cells.swap(i, j);
+        let (cx, cy) = ((cells[i] % grid) as f64, (cells[i] / grid) as f64);
+        let center = [(cx + 0.5) * cell, (cy + 0.5) * cell];
+        let mut ring = star_ring(&mut rng, hole_vertices, center, 0.1 * cell, 0.4 * cell);
+        ring.reverse();
+        rings.push(ring);
+    }
+    rings
+}
+
+// a long thin polygon of n >= 4 vertices: a jagged bottom edge of n - 2
+// vertices from 0,0 to 1000,0, less than thickness / 2 high, under a flat
+// top edge at 'thickness'. a tiny thickness makes nearly collinear points
+pub fn sliver(seed: u64, n: usize, thickness: f64) -> Rings {
+    let mut rng = Rng::new(seed);
+    let bottom = n.max(4) - 2;
+    let mut ring: Vec<Vec<f64>> = (0..bottom)
+        .map(|i| {
+            let x = 1000.0 * i as f64 / (bottom - 1) as f64;
+            vec![x, rng.range(0.0, thickness / 2.0)]
+        })
+        .collect();
+    ring.push(vec![1000.0, thickness]);
+    ring.push(vec![0.0, thickness]);
+    vec![ring]
+}
+
+// a ring that touches itself: 'petals' triangles around 0,0, all with a
+// corner at 0,0, joined into one ring that goes through 0,0 once per
+// petal. 3 * petals vertices
+pub fn self_touching(seed: u64, petals: usize) -> Rings {
+    let mut rng = Rng::new(seed);
+    let sector = 2.0 * PI / petals as f64;
+    let mut ring = Vec::with_capacity(3 * petals);
+    for i in 0..petals {
+        let a = sector * i as f64;
+        let width = sector * rng.range(0.2, 0.9);
+        let (r1, r2) = (rng.range(500.0, 1000.0), rng.range(500.0, 1000.0));
+        ring.push(vec![0.0, 0.0]);
+        ring.push(vec![r1 * a.cos(), r1 * a.sin()]);
+        ring.push(vec![r2 * (a + width).cos(), r2 * (a + width).sin()]);
+    }
+    vec![ring]
+}
+
+// n >= 3 points counterclockwise around 'center', one in each of n equal
+// sectors, at a random angle within it and a random radius
+fn star_ring(rng: &mut Rng, n: usize, center: [f64; 2], rmin: f64, rmax: f64) -> Vec<Vec<f64>> {
+    let sector = 2.0 * PI / n.max(3) as f64;
+    (0..n.max(3))
+        .map(|i| {
+            let a = sector * (i as f64 + rng.range(0.1, 0.9));
+            let r = rng.range(rmin, rmax);
+            vec![center[0] + r * a.cos(), center[1] + r * a.sin()]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{earcut, flatten, verify};
+    use super::*;
+
+    // the number of triangles, and whether verify() is happy with them
+    fn triangulate(rings: &Rings) -> (usize, bool) {
+        let (data, holes, dims) = flatten(rings);
+        let triangles = earcut(&data, &holes, dims);
+        let v = verify(&data, &holes, dims, &triangles);
+        (triangles.len() / 3, v.is_valid())
+    }
+
+    #[test]
+    fn test_rng() {
+        let (mut a, mut b) = (Rng::new(7), Rng::new(7));
+        assert!((0..10).all(|_| a.next_u64() == b.next_u64()));
+        assert!(Rng::new(7).next_u64() != Rng::new(8).next_u64());
+        let mut r = Rng::new(1);
+        assert!((0..1000).all(|_| {
+            let x = r.next_f64();
+            (0.0..1.0).contains(&x) && r.below(3) < 3
+        }));
+    }
+
+    #[test]
+    fn test_generators() {
+        assert!(star(1, 100) == star(1, 100) && star(1, 100) != star(2, 100));
+        assert!(star(1, 100)[0].len() == 100);
+        assert!(triangulate(&star(1, 100)) == (98, true));
+        assert!(triangulate(&spiral(1, 200, 3.0)) == (198, true));
+        assert!(comb(1, 10)[0].len() == 42);
+        assert!(comb(1, 0) == comb(1, 1) && comb(1, 1)[0].len() == 6);
+        assert!(triangulate(&comb(1, 10)) == (40, true));
+        assert!(hilbert(3)[0].len() == 66);
+        assert!(triangulate(&hilbert(3)) == (64, true));
+        let h = holes(1, 10, 5);
+        assert!(h.len() == 11 && h[1..].iter().all(|r| r.len() == 5));
+        assert!(triangulate(&h) == (4 + 50 + 2 * 10 - 2, true));
+        assert!(triangulate(&sliver(1, 50, 1.0)) == (48, true));
+        // the other visits of 0,0 keep every petal from being an ear, and
+        // earcut gives up on them
+        assert!(self_touching(1, 6)[0].len() == 18);
+        assert!(triangulate(&self_touching(1, 6)) == (0, false));
+    }
+
+    #[test]
+    fn test_hilbert_point() {
+        let points: Vec<(usize, usize)> = (0..4).map(|d| hilbert_point(2, d)).collect();
+        assert!(points == vec![(0, 0), (0, 1), (1, 1), (1, 0)]);
+        assert!(hilbert_point(8, 63) == (7, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "hilbert order 0 is not in 1..=15")]
+    fn test_hilbert_order() {
+        hilbert(0);
+    }
+}
@@ -1,5 +1,37 @@
 #![allow(dead_code)]
 
+#[cfg(feature = "check")]
+mod check;
+mod constraints;
+mod delaunay;
+mod diagnostics;
+pub mod generate;
+mod minimize;
+mod optimal;
+mod quality;
+mod refine;
+mod robust;
+mod steiner;
+#[cfg(feature = "trace")]
+mod trace;
+mod verify;
+
+pub use constraints::earcut_with_constraints;
+pub use diagnostics::{Diagnostics, Dropped};
+pub use minimize::{fixture_json, minimize};
+pub use optimal::Objective;
+pub use quality::{quality_report, triangle_quality, Histogram, QualityReport, TriangleQuality};
+pub use refine::{earcut_with_refinement, Refinement};
+pub use steiner::{earcut_with_steiner, Rejected};
+#[cfg(feature = "trace")]
+pub use trace::{trace_json, TraceEvent};
+pub use verify::{deviation_report, verify, DeviationReport, Overlap, RingDeviation, Verification};
+
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 static DIM: usize = 2;
 static NULL: usize = 0;
 //static DEBUG: usize = 4;
@@ -19,6 +51,7 @@ struct Node {
     prevz_idx: NodeIdx, // previous node in z-order
     nextz_idx: NodeIdx, // next node in z-order
     steiner: bool,      // indicates whether this is a steiner point
+    removed: bool,      // taken out of its ring by remove_node
     idx: NodeIdx,       // index within LinkedLists vector that holds all nodes
 }
 
@@ -34,6 +67,7 @@ impl Node {
             nextz_idx: NULL,
             prevz_idx: NULL,
             steiner: false,
+            removed: false,
             idx: idx,
         }
     }
@@ -46,15 +80,495 @@ pub struct LinkedLists {
     miny: f64,
     maxx: f64,
     maxy: f64,
+    // local frame the nodes are stored in, x_node = (x_data - ox) * scale
+    ox: f64,
+    oy: f64,
+    scale: f64,
     usehash: bool,
+    opts: EarcutOptions,
+    ops: u64, // work done so far, see tick()
+    stats: EarcutStats,
+    timed: bool, // whether to fill in the times in stats
+    diagnostics: Option<Diagnostics>, // notes, when earcut_with_diagnostics asks
+    // the first vertex of the constraint polylines of
+    // earcut_with_constraints, if any
+    constraints_from: VertIdx,
+    joins: Vec<Join>, // the ends of those polylines that are on a ring
+    #[cfg(feature = "trace")]
+    trace: Option<Vec<TraceEvent>>, // events, when earcut_with_trace asks
+}
+
+// an end of a constraint polyline on a ring edge, from vertex a to b, or
+// at vertex a, when b is a too. 'from' is the vertex next to the end on
+// the polyline. 'split' is set on the second end of a polyline with both
+// on rings: joining it cuts the polygon in two
+#[derive(Clone, Copy, Debug)]
+struct Join {
+    end: VertIdx,
+    from: VertIdx,
+    a: VertIdx,
+    b: VertIdx,
+    split: bool,
+}
+
+// what earcut_with_stats found out about a triangulation. all counts
+// are totals over the whole call
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EarcutStats {
+    pub nodes: usize,          // nodes created for input vertices
+    pub holes: usize,          // holes bridged to the outer ring
+    pub bridge_nodes: usize,   // nodes added by hole bridges and splits
+    pub ears: [usize; 3],      // ears clipped in pass 0, 1 and 2
+    pub cure_fixes: usize,     // triangles made by cure_local_intersections
+    pub splits: usize,         // polygons cut in two by split_earcut
+    pub flips: usize,          // diagonals flipped by the delaunay pass
+    pub optimal: usize,        // polygons given an optimal triangulation
+    pub degenerate: usize,     // triangles of zero area dropped
+    pub build_time: Duration,  // building the linked lists
+    pub hole_time: Duration,   // bridging the holes
+    pub zorder_time: Duration, // z-order indexing
+    pub clip_time: Duration,   // the ear clipping passes, without indexing
+}
+
+// settings for earcut_with_options. the defaults reproduce plain earcut()
+#[derive(Clone, Debug)]
+pub struct EarcutOptions {
+    strict: bool,
+    predicates: Predicates,
+    normalize: bool,
+    snap: Snap,
+    hash_threshold: usize,
+    zorder_bits: u32,
+    skip_after_ear: bool,
+    best_ears: bool,
+    optimal: Option<Objective>,
+    filter_pass: bool,
+    cure_pass: bool,
+    split_pass: bool,
+    delaunay: bool,
+    drop_degenerate: bool,
+    max_ops: u64,
+    deadline: Option<Instant>,
+    cancel: Option<CancelToken>,
 }
 
+impl Default for EarcutOptions {
+    fn default() -> EarcutOptions {
+        EarcutOptions {
+            strict: false,
+            predicates: Predicates::Fast,
+            normalize: false,
+            snap: Snap::Off,
+            hash_threshold: 40,
+            zorder_bits: 15,
+            skip_after_ear: true,
+            best_ears: false,
+            optimal: None,
+            filter_pass: true,
+            cure_pass: true,
+            split_pass: true,
+            delaunay: false,
+            drop_degenerate: false,
+            max_ops: u64::MAX,
+            deadline: None,
+            cancel: None,
+        }
+    }
+}
+
+impl EarcutOptions {
+    pub fn new() -> EarcutOptions {
+        EarcutOptions::default()
+    }
+
+    // in strict mode the triangulator refuses to 'heal' a polygon. the
+    // first pass may only drop duplicate and collinear points; if ears are
+    // still missing after that, or a hole can not be bridged to the outer
+    // ring, an error is returned instead of running
+    // cure_local_intersections or split_earcut
+    pub fn strict(mut self, strict: bool) -> EarcutOptions {
+        self.strict = strict;
+        self
+    }
+
+    // evaluate every orientation test (area, point_in_triangle,
+    // pseudo_intersects, locally_inside, middle_inside and the hole bridge
+    // ray) with adaptive exact arithmetic, so decisions on nearly collinear
+    // points are consistent with each other. slower; the default uses
+    // plain f64 math, same as the javascript earcut
+    pub fn robust(mut self, robust: bool) -> EarcutOptions {
+        self.predicates = match robust {
+            true => Predicates::Robust,
+            false => Predicates::Fast,
+        };
+        self
+    }
+
+    // besides moving the polygon to a local origin, which is always done,
+    // scale it by a power of two so its bounding box fits the unit square.
+    // the scaling is exact, it changes no decision unless products of
+    // coordinates would overflow or underflow, ie. for coordinates beyond
+    // about 1e150 or below 1e-150. ignored for integer coordinates
+    pub fn normalize(mut self, normalize: bool) -> EarcutOptions {
+        self.normalize = normalize;
+        self
+    }
+
+    // before triangulating, give every vertex that is within 'epsilon'
+    // of an earlier vertex the coordinates of that vertex, so the two are
+    // treated as one point. the triangles still use the original vertex
+    // indices. replaces snap_to_grid
+    pub fn weld(mut self, epsilon: f64) -> EarcutOptions {
+        self.snap = Snap::Weld(epsilon);
+        self
+    }
+
+    // before triangulating, round every coordinate to the nearest
+    // multiple of 'cell'. replaces weld
+    pub fn snap_to_grid(mut self, cell: f64) -> EarcutOptions {
+        self.snap = Snap::Grid(cell);
+        self
+    }
+
+    // polygons with at least this many vertices, holes included, are
+    // triangulated with the z-order hash, which finds the points near an
+    // ear quickly but costs some setup. default 40
+    pub fn hash_threshold(mut self, vertices: usize) -> EarcutOptions {
+        self.hash_threshold = vertices;
+        self
+    }
+
+    // always (true) or never (false) use the z-order hash, regardless of
+    // the size of the polygon. shorthand for hash_threshold
+    pub fn hashed(self, hashed: bool) -> EarcutOptions {
+        match hashed {
+            true => self.hash_threshold(0),
+            false => self.hash_threshold(usize::MAX),
+        }
+    }
+
+    // bits per axis of the z-order hash, 1 to 15. fewer bits put more
+    // points in each hash cell. default 15, the most an i32 z value holds
+    pub fn zorder_bits(mut self, bits: u32) -> EarcutOptions {
+        self.zorder_bits = bits.clamp(1, 15);
+        self
+    }
+
+    // after clipping an ear, continue the search two vertices further
+    // instead of at the next one, which gives fewer sliver triangles.
+    // default true
+    pub fn skip_after_ear(mut self, skip: bool) -> EarcutOptions {
+        self.skip_after_ear = skip;
+        self
+    }
+
+    // instead of clipping each ear as it comes along the ring, clip the
+    // best shaped one first, then the best of what is left, and so on.
+    // fewer slivers, but slower: every vertex is tested up front, and the
+    // whole ring again whenever the ears run out. skip_after_ear doesn't
+    // apply. default false
+    pub fn best_ears(mut self, best: bool) -> EarcutOptions {
+        self.best_ears = best;
+        self
+    }
+
+    // instead of clipping ears, find the triangulation that is best by
+    // 'objective', by dynamic programming in time cubic in the number of
+    // vertices. only done for polygons on the unhashed path, below
+    // hash_threshold, of at most 50 vertices, that are simple: no holes,
+    // and a ring that doesn't cross or touch itself. others, and polygons
+    // whose every triangulation has a triangle of zero area, are ear
+    // clipped. default off
+    pub fn optimal(mut self, objective: Objective) -> EarcutOptions {
+        self.optimal = Some(objective);
+        self
+    }
+
+    // when no ear is left, earcut tries in turn: dropping duplicate and
+    // collinear points (filter), clipping small self-intersections
+    // (cure), and cutting the polygon in two along a diagonal (split).
+    // each can be turned off; with all three off, whatever is left
+    // unclipped is silently dropped. all default to true
+    pub fn filter_pass(mut self, filter: bool) -> EarcutOptions {
+        self.filter_pass = filter;
+        self
+    }
+
+    pub fn cure_pass(mut self, cure: bool) -> EarcutOptions {
+        self.cure_pass = cure;
+        self
+    }
+
+    pub fn split_pass(mut self, split: bool) -> EarcutOptions {
+        self.split_pass = split;
+        self
+    }
+
+    // after clipping, flip diagonals until the triangles are constrained
+    // delaunay, with the ring edges as the constraints: of two triangles
+    // sharing a diagonal, neither has the other's far corner inside its
+    // circumcircle. gives fewer long thin triangles, with the same
+    // vertices, as many triangles, and the same ring edges. default false
+    pub fn delaunay(mut self, delaunay: bool) -> EarcutOptions {
+        self.delaunay = delaunay;
+        self
+    }
+
+    // leave the triangles of zero area out of the result, tested exactly
+    // on the coordinates as triangulated, after any weld or snap_to_grid.
+    // earcut makes them where vertices are collinear, and where it heals
+    // a polygon that crosses itself. default false
+    pub fn drop_degenerate(mut self, drop: bool) -> EarcutOptions {
+        self.drop_degenerate = drop;
+        self
+    }
+
+    // give up with EarcutError::OverBudget after this many operations;
+    // an operation is one ear or diagonal tested, or one hole bridged.
+    // split_earcut can take a number of operations cubic in the number
+    // of vertices on bad input, a good polygon needs a few per vertex
+    pub fn max_operations(mut self, ops: u64) -> EarcutOptions {
+        self.max_ops = ops;
+        self
+    }
+
+    // give up with EarcutError::OverBudget once this time has passed.
+    // the clock is read every 1024 operations
+    pub fn deadline(mut self, deadline: Instant) -> EarcutOptions {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    // give up with EarcutError::Cancelled once the token is cancelled,
+    // from any thread. like the deadline, checked every 1024 operations
+    pub fn cancel_token(mut self, token: &CancelToken) -> EarcutOptions {
+        self.cancel = Some(token.clone());
+        self
+    }
+}
+
+// lets another thread stop a running earcut_with_options, see
+// EarcutOptions::cancel_token. clones share the same flag
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// how vertices are merged before triangulating, see EarcutOptions::weld
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Snap {
+    #[default]
+    Off,
+    Weld(f64),
+    Grid(f64),
+}
+
+// how the signs of the geometric tests are computed. Integer is used
+// automatically for integer input, see Coord
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+enum Predicates {
+    #[default]
+    Fast,
+    Robust,
+    Integer,
+}
+
+impl Predicates {
+    // same as area(), but with an exact sign in robust mode
+    #[inline(always)]
+    fn area(self, p: &Node, q: &Node, r: &Node) -> f64 {
+        match self {
+            Predicates::Fast => area(p, q, r),
+            Predicates::Robust => -robust::orient2d(p.x, p.y, q.x, q.y, r.x, r.y),
+            Predicates::Integer => -robust::orient2d_int(
+                p.x as i64, p.y as i64, q.x as i64, q.y as i64, r.x as i64, r.y as i64,
+            ) as f64,
+        }
+    }
+
+    #[inline(always)]
+    fn point_in_triangle(self, a: &Node, b: &Node, c: &Node, p: &Node) -> bool {
+        match self {
+            Predicates::Fast => point_in_triangle(a, b, c, p),
+            Predicates::Robust => {
+                robust::orient2d(p.x, p.y, c.x, c.y, a.x, a.y) >= 0.0
+                    && robust::orient2d(p.x, p.y, a.x, a.y, b.x, b.y) >= 0.0
+                    && robust::orient2d(p.x, p.y, b.x, b.y, c.x, c.y) >= 0.0
+            }
+            Predicates::Integer => {
+                let (ax, ay, bx, by) = (a.x as i64, a.y as i64, b.x as i64, b.y as i64);
+                let (cx, cy, px, py) = (c.x as i64, c.y as i64, p.x as i64, p.y as i64);
+                robust::orient2d_int(px, py, cx, cy, ax, ay) >= 0
+                    && robust::orient2d_int(px, py, ax, ay, bx, by) >= 0
+                    && robust::orient2d_int(px, py, bx, by, cx, cy) >= 0
+            }
+        }
+    }
+}
+
+// coordinate types earcut_with_options accepts. integer coordinates are
+// always triangulated with exact integer arithmetic; they are held in the
+// f64 fields of the nodes, so i64 values must be within +/- 2^52
+pub trait Coord: Copy {
+    const INTEGER: bool;
+    fn to_f64(self) -> f64;
+    // nearest value of this type, used by snap_to_grid
+    fn from_f64(x: f64) -> Self;
+    fn in_range(self) -> bool {
+        true
+    }
+    // twice the signed area of the ring data[start..end], measured in the
+    // local frame of 'll'. used for the winding of rings, so the sign must
+    // be right
+    fn signed_area(data: &[Self], start: usize, end: usize, ll: &LinkedLists) -> f64;
+}
+
+impl Coord for f64 {
+    const INTEGER: bool = false;
+    fn to_f64(self) -> f64 {
+        self
+    }
+    // NaN and infinity would make every comparison along the way
+    // meaningless
+    fn in_range(self) -> bool {
+        self.is_finite()
+    }
+    fn from_f64(x: f64) -> f64 {
+        x
+    }
+    fn signed_area(data: &[f64], start: usize, end: usize, ll: &LinkedLists) -> f64 {
+        signed_area_local(data, start, end, ll.ox, ll.oy, ll.scale)
+    }
+}
+
+impl Coord for i32 {
+    const INTEGER: bool = true;
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+    fn from_f64(x: f64) -> i32 {
+        x.round() as i32
+    }
+    fn signed_area(data: &[i32], start: usize, end: usize, _: &LinkedLists) -> f64 {
+        signed_area_exact(data, start, end)
+    }
+}
+
+impl Coord for i64 {
+    const INTEGER: bool = true;
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+    fn from_f64(x: f64) -> i64 {
+        x.round() as i64
+    }
+    fn in_range(self) -> bool {
+        self.unsigned_abs() <= 1 << 52
+    }
+    fn signed_area(data: &[i64], start: usize, end: usize, _: &LinkedLists) -> f64 {
+        signed_area_exact(data, start, end)
+    }
+}
+
+// reasons earcut_with_options can fail. rings are given as lists of
+// vertex indices into the input data, in linked list order
+#[derive(Clone, Debug, PartialEq)]
+pub enum EarcutError {
+    // no more ears could be found; holds what is left of the polygon
+    Unclipped { ring: Vec<VertIdx> },
+    // no bridge from the hole to the outer ring could be found
+    NoHoleBridge {
+        hole: Vec<VertIdx>,
+        outer: Vec<VertIdx>,
+    },
+    // an integer coordinate is too big to be handled exactly, or a float
+    // coordinate is NaN or infinite
+    OutOfRange { vertex: VertIdx },
+    // hole_indices[hole] is smaller than the one before it, or past the
+    // last vertex
+    BadHoleIndex { hole: usize },
+    // the operation budget or the deadline ran out; holds the triangles
+    // found so far
+    OverBudget { triangles: Vec<usize> },
+    // the cancel token was cancelled; holds the triangles found so far
+    Cancelled { triangles: Vec<usize> },
+    // earcut_with_refinement got triangles that are flat, clockwise or
+    // overlapping, from bad input, and can't refine them; holds them
+    Unrefined { triangles: Vec<usize> },
+}
+
+impl std::fmt::Display for EarcutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            EarcutError::Unclipped { ring } => {
+                write!(f, "no ears left to clip in ring {:?}", ring)
+            }
+            EarcutError::NoHoleBridge { hole, outer } => write!(
+                f,
+                "no bridge from hole {:?} to outer ring {:?}",
+                hole, outer
+            ),
+            EarcutError::OutOfRange { vertex } => {
+                write!(f, "coordinates of vertex {} are out of range", vertex)
+            }
+            EarcutError::BadHoleIndex { hole } => {
+                write!(f, "hole index {} is out of order or out of range", hole)
+            }
+            EarcutError::OverBudget { triangles } => write!(
+                f,
+                "ran out of budget after {} triangles",
+                triangles.len() / 3
+            ),
+            EarcutError::Cancelled { triangles } => {
+                write!(f, "cancelled after {} triangles", triangles.len() / 3)
+            }
+            EarcutError::Unrefined { triangles } => write!(
+                f,
+                "{} triangles are not a clean triangulation, and can't be refined",
+                triangles.len() / 3
+            ),
+        }
+    }
+}
+
+impl std::error::Error for EarcutError {}
+
 macro_rules! dlog {
 	($loglevel:expr, $($s:expr),*) => (
 		if DEBUG>=$loglevel { print!("{}:",$loglevel); println!($($s),+); }
 	)
 }
 
+// record a TraceEvent, if tracing. without the trace feature the event
+// expression isn't even compiled
+#[cfg(feature = "trace")]
+macro_rules! trace {
+    ($ll:expr, $event:expr) => {
+        if $ll.trace.is_some() {
+            let event = $event;
+            if let Some(trace) = $ll.trace.as_mut() {
+                trace.push(event);
+            }
+        }
+    };
+}
+#[cfg(not(feature = "trace"))]
+macro_rules! trace {
+    ($ll:expr, $event:expr) => {};
+}
+
 // macro design: built so we can easily swap unchecked for checked,
 // to test speed. and because unsafe get_ funcs have different meaning
 // than bracket operator (indexing operator) nodes[index]
@@ -132,6 +646,7 @@ impl LinkedLists {
         };
         let result = p.idx;
         self.nodes.push(p);
+        self.stats.nodes += 1;
         return result;
     }
     fn remove_node(&mut self, p_idx: NodeIdx) {
@@ -143,6 +658,18 @@ impl LinkedLists {
         nodemut!(self, ni).prev_idx = pi;
         nodemut!(self, pz).nextz_idx = nz;
         nodemut!(self, nz).prevz_idx = pz;
+        // the last node of a ring stays linked to itself, and in use: a
+        // hole of one point is bridged like that, as a steiner point
+        if pi != p_idx {
+            nodemut!(self, p_idx).removed = true;
+        }
+        #[cfg(feature = "check")]
+        {
+            if pi != p_idx {
+                let z = if pz != NULL { pz } else { nz };
+                check::check_links(self, "remove_node", pi, z);
+            }
+        }
     }
     fn new(size_hint: usize) -> LinkedLists {
         let mut ll = LinkedLists {
@@ -152,7 +679,19 @@ impl LinkedLists {
             miny: std::f64::MAX,
             maxx: std::f64::MIN,
             maxy: std::f64::MIN,
+            ox: 0.0,
+            oy: 0.0,
+            scale: 1.0,
             usehash: true,
+            opts: EarcutOptions::default(),
+            ops: 0,
+            stats: EarcutStats::default(),
+            timed: false,
+            diagnostics: None,
+            constraints_from: usize::MAX,
+            joins: Vec::new(),
+            #[cfg(feature = "trace")]
+            trace: None,
         };
         // ll.nodes[0] is the NULL node. For example usage, see remove_node()
         ll.nodes.push(Node {
@@ -165,6 +704,7 @@ impl LinkedLists {
             nextz_idx: 0,
             prevz_idx: 0,
             steiner: false,
+            removed: false,
             idx: 0,
         });
         ll
@@ -243,142 +783,516 @@ fn compare_x(a: &Node, b: &Node) -> std::cmp::Ordering {
 
 // link every hole into the outer loop, producing a single-ring polygon
 // without holes
-fn eliminate_holes(
+fn eliminate_holes<T: Coord>(
     ll: &mut LinkedLists,
-    data: &Vec<f64>,
+    data: &[T],
     hole_indices: &Vec<usize>,
     inouter_node: NodeIdx,
-) -> NodeIdx {
+) -> Result<NodeIdx, EarcutError> {
     let mut outer_node = inouter_node;
     let mut queue: Vec<Node> = Vec::new();
+    let started = clock(ll);
     for i in 0..hole_indices.len() {
         let start = hole_indices[i] * DIM;
         let end = if i < (hole_indices.len() - 1) {
             hole_indices[i + 1] * DIM
         } else {
-            data.len()
+            data.len() - data.len() % DIM
         };
         let (list, leftmost_idx) = linked_list_add_contour(ll, &data, start, end, false);
+        // a hole without vertices
+        if list == NULL {
+            continue;
+        }
+        trace!(ll, TraceEvent::Ring { ring: ring_indices(ll, list), hole: true });
         if list == noderef!(ll, list).next_idx {
             nodemut!(ll, list).steiner = true;
+            if let Some(d) = ll.diagnostics.as_mut() {
+                d.steiner.push(noderef!(ll, list).i);
+            }
+        } else if hole_indices[i] >= ll.constraints_from {
+            // a constraint polyline, doubled back on itself. its tips have
+            // no area, and would otherwise be filtered out
+            let mut p = list;
+            loop {
+                nodemut!(ll, p).steiner = true;
+                p = noderef!(ll, p).next_idx;
+                if p == list {
+                    break;
+                }
+            }
+            // one that ends on a ring is joined to it there instead
+            let range = hole_indices[i]..end / DIM;
+            if ll.joins.iter().any(|j| !j.split && range.contains(&j.end)) {
+                continue;
+            }
         }
         queue.push(node!(ll, leftmost_idx).clone());
     }
+    if !ll.joins.is_empty() {
+        // a polyline that can't be joined is bridged like any other hole
+        for tip in join_first_ends(ll) {
+            queue.push(node!(ll, tip).clone());
+        }
+        // a hole that a polyline was joined to may reach further left now
+        for h in queue.iter_mut() {
+            *h = ll.iter(h.idx..h.idx).min_by(|a, b| compare_x(a, b)).unwrap().clone();
+        }
+    }
+    ll.stats.build_time += elapsed(started);
 
+    let started = clock(ll);
     queue.sort_by(compare_x);
 
     // process holes from left to right
     for i in 0..queue.len() {
-        eliminate_hole(ll, queue[i].idx, outer_node);
+        tick(ll)?;
+        let bridge = eliminate_hole(ll, queue[i].idx, outer_node)?;
+        // filtering around the bridge can remove outer_node. carry on from
+        // the bridge instead
+        if node!(ll, outer_node).removed {
+            outer_node = bridge;
+        }
         let nextidx = next!(ll, outer_node).idx;
         outer_node = filter_points(ll, outer_node, nextidx);
     }
-    outer_node
+    ll.stats.hole_time += elapsed(started);
+    Ok(outer_node)
 } // elim holes
 
 // minx, miny and invsize are later used to transform coords
-// into integers for z-order calculation
-fn calc_invsize(minx: f64, miny: f64, maxx: f64, maxy: f64) -> f64 {
+// into integers, of 'bits' bits, for z-order calculation
+fn calc_invsize(minx: f64, miny: f64, maxx: f64, maxy: f64, bits: u32) -> f64 {
     let invsize = f64::max(maxx - minx, maxy - miny);
     match invsize == 0.0 {
         true => 0.0,
-        false => 32767.0 / invsize,
+        false => ((1 << bits) - 1) as f64 / invsize,
+    }
+}
+
+// invsize for integer coordinates; a power of two, so that the scaling
+// in zorder() is the same as shifting the integers right until they fit
+// in 'bits' bits
+fn calc_invsize_int(minx: f64, miny: f64, maxx: f64, maxy: f64, bits: u32) -> f64 {
+    let size = f64::max(maxx - minx, maxy - miny) as u64;
+    let shift = (64 - size.leading_zeros() as i32 - bits as i32).max(0);
+    match size == 0 {
+        true => 0.0,
+        false => 0.5f64.powi(shift),
+    }
+}
+
+// the current time, when stats are timed
+fn clock(ll: &LinkedLists) -> Option<Instant> {
+    match ll.timed {
+        true => Some(Instant::now()),
+        false => None,
+    }
+}
+
+fn elapsed(started: Option<Instant>) -> Duration {
+    started.map_or(Duration::ZERO, |t| t.elapsed())
+}
+
+// count one operation against the budget. the clock and the cancel token
+// are only looked at every 1024 operations, to keep this cheap
+#[inline(always)]
+fn tick(ll: &mut LinkedLists) -> Result<(), EarcutError> {
+    ll.ops += 1;
+    match ll.ops & 1023 == 0 || ll.ops > ll.opts.max_ops {
+        true => check_budget(ll),
+        false => Ok(()),
+    }
+}
+
+#[cold]
+fn check_budget(ll: &LinkedLists) -> Result<(), EarcutError> {
+    // the triangles are filled in by earcut_with_options
+    let triangles = Vec::new();
+    if ll.opts.cancel.as_ref().is_some_and(|c| c.is_cancelled()) {
+        Err(EarcutError::Cancelled { triangles })
+    } else if ll.ops > ll.opts.max_ops || ll.opts.deadline.is_some_and(|d| Instant::now() >= d) {
+        Err(EarcutError::OverBudget { triangles })
+    } else {
+        Ok(())
     }
 }
 
 // main ear slicing loop which triangulates a polygon (given as a linked
-// list)
+// list). runs the passes, and the halves split_earcut makes, from a
+// stack rather than by recursion, so that a polygon that is split over
+// and over can't overflow a small thread stack. the order is the same
+// as the recursive javascript version: each half completely, first a,
+// then c
+fn earcut_linked(
+    ll: &mut LinkedLists,
+    ear_idx: NodeIdx,
+    triangles: &mut Vec<usize>,
+    pass: usize,
+    hashed: bool,
+) -> Result<(), EarcutError> {
+    let mut stack = vec![(ear_idx, pass, hashed)];
+    while let Some((ear_idx, pass, hashed)) = stack.pop() {
+        let step = match hashed {
+            true => earcut_pass_hashed(ll, ear_idx, triangles, pass)?,
+            false => earcut_pass_unhashed(ll, ear_idx, triangles, pass)?,
+        };
+        match step {
+            Step::Done => {}
+            Step::Pass(idx, pass) => {
+                trace!(ll, TraceEvent::Pass { pass, ring: ring_indices(ll, idx) });
+                stack.push((idx, pass, hashed))
+            }
+            // split halves always use the hash
+            Step::Split(a, c) => {
+                stack.push((c, 0, true));
+                stack.push((a, 0, true));
+            }
+        }
+    }
+    Ok(())
+}
+
 fn earcut_linked_hashed(
+    ll: &mut LinkedLists,
+    ear_idx: NodeIdx,
+    triangles: &mut Vec<usize>,
+    pass: usize,
+) -> Result<(), EarcutError> {
+    earcut_linked(ll, ear_idx, triangles, pass, true)
+}
+
+fn earcut_linked_unhashed(
+    ll: &mut LinkedLists,
+    ear_idx: NodeIdx,
+    triangles: &mut Vec<usize>,
+    pass: usize,
+) -> Result<(), EarcutError> {
+    earcut_linked(ll, ear_idx, triangles, pass, false)
+}
+
+// what earcut_linked has left to do after one pass over a ring
+enum Step {
+    Done,
+    // run the given pass on the ring at the node
+    Pass(NodeIdx, usize),
+    // triangulate both halves of a split polygon, from pass 0
+    Split(NodeIdx, NodeIdx),
+}
+
+// one pass of the ear slicing loop
+fn earcut_pass_hashed(
     ll: &mut LinkedLists,
     mut ear_idx: NodeIdx,
     triangles: &mut Vec<usize>,
     pass: usize,
-) {
+) -> Result<Step, EarcutError> {
     // interlink polygon nodes in z-order
     if pass == 0 {
+        let started = clock(ll);
         index_curve(ll, ear_idx);
+        ll.stats.zorder_time += elapsed(started);
+        trace!(ll, TraceEvent::ZOrder { order: trace::zorder_indices(ll, ear_idx) });
     }
     // iterate through ears, slicing them one by one
     let mut stop_idx = ear_idx;
     let mut prev_idx = 0;
     let mut next_idx = node!(ll, ear_idx).next_idx;
+    if ll.opts.best_ears {
+        // as if the loop below had stopped at what is left
+        next_idx = clip_best_ears(ll, ear_idx, triangles, pass, true)?;
+        ear_idx = node!(ll, next_idx).prev_idx;
+        prev_idx = node!(ll, ear_idx).prev_idx;
+        stop_idx = next_idx;
+    }
     while stop_idx != next_idx {
+        tick(ll)?;
         prev_idx = node!(ll, ear_idx).prev_idx;
         next_idx = node!(ll, ear_idx).next_idx;
         if is_ear_hashed(ll, prev_idx, ear_idx, next_idx) {
+            ll.stats.ears[pass.min(2)] += 1;
+            trace!(ll, TraceEvent::Ear {
+                a: node!(ll, prev_idx).i,
+                b: node!(ll, ear_idx).i,
+                c: node!(ll, next_idx).i,
+            });
             triangles.push(node!(ll, prev_idx).i);
             triangles.push(node!(ll, ear_idx).i);
             triangles.push(node!(ll, next_idx).i);
             ll.remove_node(ear_idx);
             // skipping the next vertex leads to less sliver triangles
-            ear_idx = node!(ll, next_idx).next_idx;
+            if ll.opts.skip_after_ear {
+                ear_idx = node!(ll, next_idx).next_idx;
+            } else {
+                // move next_idx along too, or the loop would stop here
+                ear_idx = next_idx;
+                next_idx = node!(ll, ear_idx).next_idx;
+            }
             stop_idx = ear_idx;
         } else {
+            trace!(ll, TraceEvent::NotEar {
+                a: node!(ll, prev_idx).i,
+                b: node!(ll, ear_idx).i,
+                c: node!(ll, next_idx).i,
+                blocker: trace::ear_blocker(ll, prev_idx, ear_idx, next_idx),
+            });
             ear_idx = next_idx;
         }
     }
 
     if prev_idx == next_idx {
-        return;
+        // the last two nodes; in a triangle already, unless the ring had
+        // nothing to clip
+        ll.note_ring_dropped(next_idx, Dropped::DegenerateRing);
+        return Ok(Step::Done);
     };
     // if we looped through the whole remaining polygon and can't
     // find any more ears
     if pass == 0 {
-        let tmp = filter_points(ll, next_idx, NULL);
-        earcut_linked_hashed(ll, tmp, triangles, 1);
+        let tmp = match ll.opts.filter_pass {
+            true => filter_points(ll, next_idx, NULL),
+            false => next_idx,
+        };
+        Ok(Step::Pass(tmp, 1))
+    } else if ll.opts.strict {
+        // what remains after filtering a zero-area polygon is not an error
+        let ring = ring_indices(ll, next_idx);
+        ll.note_ring_dropped(next_idx, Dropped::Unclipped);
+        match ring.len() < 3 {
+            true => Ok(Step::Done),
+            false => Err(EarcutError::Unclipped { ring }),
+        }
     } else if pass == 1 {
-        ear_idx = cure_local_intersections(ll, next_idx, triangles);
-        earcut_linked_hashed(ll, ear_idx, triangles, 2);
-    } else if pass == 2 {
-        split_earcut(ll, next_idx, triangles);
+        ear_idx = match ll.opts.cure_pass {
+            true => cure_local_intersections(ll, next_idx, triangles),
+            false => next_idx,
+        };
+        Ok(Step::Pass(ear_idx, 2))
+    } else if pass == 2 && ll.opts.split_pass {
+        split_earcut(ll, next_idx)
+    } else {
+        ll.note_ring_dropped(next_idx, Dropped::Unclipped);
+        Ok(Step::Done)
     }
 }
 
 
-// main ear slicing loop which triangulates a polygon (given as a linked
-// list)
-fn earcut_linked_unhashed(
+// one pass of the ear slicing loop, without the z-order hash
+fn earcut_pass_unhashed(
     ll: &mut LinkedLists,
     mut ear_idx: NodeIdx,
     triangles: &mut Vec<usize>,
     pass: usize,
-) {
+) -> Result<Step, EarcutError> {
     // iterate through ears, slicing them one by one
     let mut stop_idx = ear_idx;
     let mut prev_idx = 0;
     let mut next_idx = node!(ll, ear_idx).next_idx;
+    if ll.opts.best_ears {
+        // as if the loop below had stopped at what is left
+        next_idx = clip_best_ears(ll, ear_idx, triangles, pass, false)?;
+        ear_idx = node!(ll, next_idx).prev_idx;
+        prev_idx = node!(ll, ear_idx).prev_idx;
+        stop_idx = next_idx;
+    }
     while stop_idx != next_idx {
+        tick(ll)?;
         prev_idx = node!(ll, ear_idx).prev_idx;
         next_idx = node!(ll, ear_idx).next_idx;
         if is_ear(ll, prev_idx, ear_idx, next_idx) {
+            ll.stats.ears[pass.min(2)] += 1;
+            trace!(ll, TraceEvent::Ear {
+                a: node!(ll, prev_idx).i,
+                b: node!(ll, ear_idx).i,
+                c: node!(ll, next_idx).i,
+            });
             triangles.push(node!(ll, prev_idx).i);
             triangles.push(node!(ll, ear_idx).i);
             triangles.push(node!(ll, next_idx).i);
             ll.remove_node(ear_idx);
             // skipping the next vertex leads to less sliver triangles
-            ear_idx = node!(ll, next_idx).next_idx;
+            if ll.opts.skip_after_ear {
+                ear_idx = node!(ll, next_idx).next_idx;
+            } else {
+                // move next_idx along too, or the loop would stop here
+                ear_idx = next_idx;
+                next_idx = node!(ll, ear_idx).next_idx;
+            }
             stop_idx = ear_idx;
         } else {
+            trace!(ll, TraceEvent::NotEar {
+                a: node!(ll, prev_idx).i,
+                b: node!(ll, ear_idx).i,
+                c: node!(ll, next_idx).i,
+                blocker: trace::ear_blocker(ll, prev_idx, ear_idx, next_idx),
+            });
             ear_idx = next_idx;
         }
     }
 
     if prev_idx == next_idx {
-        return;
+        // the last two nodes; in a triangle already, unless the ring had
+        // nothing to clip
+        ll.note_ring_dropped(next_idx, Dropped::DegenerateRing);
+        return Ok(Step::Done);
     };
     // if we looped through the whole remaining polygon and can't
     // find any more ears
     if pass == 0 {
-        let tmp = filter_points(ll, next_idx, NULL);
-        earcut_linked_unhashed(ll, tmp, triangles, 1);
+        let tmp = match ll.opts.filter_pass {
+            true => filter_points(ll, next_idx, NULL),
+            false => next_idx,
+        };
+        Ok(Step::Pass(tmp, 1))
+    } else if ll.opts.strict {
+        // what remains after filtering a zero-area polygon is not an error
+        let ring = ring_indices(ll, next_idx);
+        ll.note_ring_dropped(next_idx, Dropped::Unclipped);
+        match ring.len() < 3 {
+            true => Ok(Step::Done),
+            false => Err(EarcutError::Unclipped { ring }),
+        }
     } else if pass == 1 {
-        ear_idx = cure_local_intersections(ll, next_idx, triangles);
-        earcut_linked_unhashed(ll, ear_idx, triangles, 2);
-    } else if pass == 2 {
-        split_earcut(ll, next_idx, triangles);
+        ear_idx = match ll.opts.cure_pass {
+            true => cure_local_intersections(ll, next_idx, triangles),
+            false => next_idx,
+        };
+        Ok(Step::Pass(ear_idx, 2))
+    } else if pass == 2 && ll.opts.split_pass {
+        split_earcut(ll, next_idx)
+    } else {
+        ll.note_ring_dropped(next_idx, Dropped::Unclipped);
+        Ok(Step::Done)
+    }
+}
+
+// clip the ears of the ring at ear_idx best first, see
+// EarcutOptions::best_ears, until it has none left. returns a node of
+// what remains. ears are queued with the neighbors they had; a clip
+// changes whether its neighbors are ears, which are tested again right
+// away, but also whether the nodes it blocked, or now blocks, are, so a
+// queued ear is tested again before it is clipped, and the whole ring
+// once the queue runs dry
+fn clip_best_ears(
+    ll: &mut LinkedLists,
+    ear_idx: NodeIdx,
+    triangles: &mut Vec<usize>,
+    pass: usize,
+    hashed: bool,
+) -> Result<NodeIdx, EarcutError> {
+    let mut queue = BinaryHeap::new();
+    let mut rest = ear_idx;
+    loop {
+        let mut p = rest;
+        loop {
+            queue_ear(ll, &mut queue, p, hashed)?;
+            p = node!(ll, p).next_idx;
+            if p == rest {
+                break;
+            }
+        }
+        if queue.is_empty() {
+            return Ok(rest);
+        }
+        while let Some((_, ear, prev, next)) = queue.pop() {
+            let n = node!(ll, ear);
+            if n.removed || n.prev_idx != prev || n.next_idx != next {
+                continue;
+            }
+            tick(ll)?;
+            let is_ear = match hashed {
+                true => is_ear_hashed(ll, prev, ear, next),
+                false => is_ear(ll, prev, ear, next),
+            };
+            if !is_ear {
+                continue;
+            }
+            ll.stats.ears[pass.min(2)] += 1;
+            trace!(ll, TraceEvent::Ear {
+                a: node!(ll, prev).i,
+                b: node!(ll, ear).i,
+                c: node!(ll, next).i,
+            });
+            triangles.push(node!(ll, prev).i);
+            triangles.push(node!(ll, ear).i);
+            triangles.push(node!(ll, next).i);
+            ll.remove_node(ear);
+            rest = next;
+            // the last two nodes
+            if node!(ll, next).next_idx == prev {
+                return Ok(rest);
+            }
+            queue_ear(ll, &mut queue, prev, hashed)?;
+            queue_ear(ll, &mut queue, next, hashed)?;
+        }
     }
 }
 
+// queue node p, with its neighbors, if it is an ear. the best shaped
+// ears come out of the queue first: their area over the sum of their
+// squared edges is the largest, 1 for an equilateral triangle after
+// scaling, toward 0 for a sliver
+fn queue_ear(
+    ll: &mut LinkedLists,
+    queue: &mut BinaryHeap<(u64, NodeIdx, NodeIdx, NodeIdx)>,
+    p: NodeIdx,
+    hashed: bool,
+) -> Result<(), EarcutError> {
+    tick(ll)?;
+    let (prev, next) = (node!(ll, p).prev_idx, node!(ll, p).next_idx);
+    let is_ear = match hashed {
+        true => is_ear_hashed(ll, prev, p, next),
+        false => is_ear(ll, prev, p, next),
+    };
+    if !is_ear {
+        trace!(ll, TraceEvent::NotEar {
+            a: node!(ll, prev).i,
+            b: node!(ll, p).i,
+            c: node!(ll, next).i,
+            blocker: trace::ear_blocker(ll, prev, p, next),
+        });
+        return Ok(());
+    }
+    let (a, b, c) = (noderef!(ll, prev), noderef!(ll, p), noderef!(ll, next));
+    let squared = |p: &Node, q: &Node| (p.x - q.x) * (p.x - q.x) + (p.y - q.y) * (p.y - q.y);
+    let quality = 2.0 * 3f64.sqrt() * area(a, b, c).abs() / (squared(a, b) + squared(b, c) + squared(c, a));
+    // the bits of a positive f64 sort like the f64
+    let key = match quality.is_finite() {
+        true => quality.to_bits(),
+        false => 0,
+    };
+    queue.push((key, p, prev, next));
+    Ok(())
+}
+
+// triangulate the ring at ear_idx the best way by 'objective', see
+// EarcutOptions::optimal, or ear clip it if that can't be done. the
+// triangles are clipped as ears, in an order that makes each one an ear
+fn earcut_optimal(
+    ll: &mut LinkedLists,
+    ear_idx: NodeIdx,
+    triangles: &mut Vec<usize>,
+    objective: Objective,
+) -> Result<(), EarcutError> {
+    let ears = match optimal::triangulate(ll, ear_idx, objective)? {
+        Some(ears) => ears,
+        None => return earcut_linked_unhashed(ll, ear_idx, triangles, 0),
+    };
+    for [prev, ear, next] in ears {
+        ll.stats.ears[0] += 1;
+        trace!(ll, TraceEvent::Ear {
+            a: node!(ll, prev).i,
+            b: node!(ll, ear).i,
+            c: node!(ll, next).i,
+        });
+        triangles.push(node!(ll, prev).i);
+        triangles.push(node!(ll, ear).i);
+        triangles.push(node!(ll, next).i);
+        ll.remove_node(ear);
+    }
+    ll.stats.optimal += 1;
+    Ok(())
+}
 
 // interlink polygon nodes in z-order
 fn index_curve(ll: &mut LinkedLists, start: NodeIdx) {
@@ -466,22 +1380,53 @@ fn sort_linked(ll: &mut LinkedLists, mut list: NodeIdx) {
 // check whether a polygon node forms a valid ear with adjacent nodes
 fn is_ear(ll: &LinkedLists, prev: NodeIdx, ear: NodeIdx, next: NodeIdx) -> bool {
     let (a, b, c) = (noderef!(ll, prev), noderef!(ll, ear), noderef!(ll, next));
-    match area(a, b, c) >= 0.0 {
+    let pred = ll.opts.predicates;
+    match pred.area(a, b, c) >= 0.0 {
         true => false, // reflex, cant be ear
         false => !ll.iter(c.next_idx..a.idx).any(|p| {
-            point_in_triangle(&a, &b, &c, &p)
-                && (area(prevref!(ll, p.idx), &p, nextref!(ll, p.idx)) >= 0.0)
+            pred.point_in_triangle(&a, &b, &c, &p)
+                && (pred.area(prevref!(ll, p.idx), &p, nextref!(ll, p.idx)) >= 0.0)
+                && !(ll.constraints_from != usize::MAX
+                    && touches_corner(pred, a, b, c, prevref!(ll, p.idx), &p, nextref!(ll, p.idx)))
         }),
     }
 }
 
 // helper for is_ear_hashed. needs manual inline (rust 2018)
 #[inline(always)]
-fn earcheck(a: &Node, b: &Node, c: &Node, prev: &Node, p: &Node, next: &Node) -> bool {
+fn earcheck(ll: &LinkedLists, a: &Node, b: &Node, c: &Node, p_idx: NodeIdx) -> bool {
+    let pred = ll.opts.predicates;
+    let (prev, p, next) = (prevref!(ll, p_idx), noderef!(ll, p_idx), nextref!(ll, p_idx));
     (p.idx != a.idx)
         && (p.idx != c.idx)
-        && point_in_triangle(&a, &b, &c, &p)
-        && area(&prev, &p, &next) >= 0.0
+        && pred.point_in_triangle(&a, &b, &c, &p)
+        && pred.area(&prev, &p, &next) >= 0.0
+        && !(ll.constraints_from != usize::MAX && touches_corner(pred, a, b, c, prev, p, next))
+}
+
+// whether p is at a corner of triangle a b c, with the ring around it,
+// prev and next, outside the triangle's angle there. a constraint
+// polyline is a slit, so the ring goes through its points more than
+// once, and through both ends of its bridge; a visit like that, which
+// only touches the triangle, shouldn't keep it from being an ear
+fn touches_corner(
+    pred: Predicates,
+    a: &Node,
+    b: &Node,
+    c: &Node,
+    prev: &Node,
+    p: &Node,
+    next: &Node,
+) -> bool {
+    // whether r is strictly inside the angle at x between y and z
+    let inside = |x: &Node, y: &Node, z: &Node, r: &Node| {
+        let same = |s: f64, t: f64| (s > 0.0 && t > 0.0) || (s < 0.0 && t < 0.0);
+        same(pred.area(x, y, r), pred.area(x, y, z)) && same(pred.area(x, z, r), pred.area(x, z, y))
+    };
+    let touches = |x: &Node, y: &Node, z: &Node| {
+        equals(p, x) && !inside(x, y, z, prev) && !inside(x, y, z, next)
+    };
+    touches(a, b, c) || touches(b, c, a) || touches(c, a, b)
 }
 
 #[inline(always)]
@@ -496,7 +1441,8 @@ fn is_ear_hashed(
         &node!(ll, ear_idx).clone(),
         &node!(ll, next_idx).clone(),
     );
-    if area(prev, ear, next) >= 0.0 {
+    let pred = ll.opts.predicates;
+    if pred.area(prev, ear, next) >= 0.0 {
         return false;
     };
 
@@ -511,26 +1457,12 @@ fn is_ear_hashed(
     let mut p = ear.prevz_idx;
     let mut n = ear.nextz_idx;
     while (p != NULL) && (node!(ll, p).z >= min_z) && (n != NULL) && (node!(ll, n).z <= max_z) {
-        if earcheck(
-            prev,
-            ear,
-            next,
-            prevref!(ll, p),
-            noderef!(ll, p),
-            nextref!(ll, p),
-        ) {
+        if earcheck(ll, prev, ear, next, p) {
             return false;
         }
         p = node!(ll, p).prevz_idx;
 
-        if earcheck(
-            prev,
-            ear,
-            next,
-            prevref!(ll, n),
-            noderef!(ll, n),
-            nextref!(ll, n),
-        ) {
+        if earcheck(ll, prev, ear, next, n) {
             return false;
         }
         n = node!(ll, n).nextz_idx;
@@ -538,14 +1470,7 @@ fn is_ear_hashed(
 
     nodemut!(ll, NULL).z = min_z - 1;
     while node!(ll, p).z >= min_z {
-        if earcheck(
-            prev,
-            ear,
-            next,
-            prevref!(ll, p),
-            noderef!(ll, p),
-            nextref!(ll, p),
-        ) {
+        if earcheck(ll, prev, ear, next, p) {
             return false;
         }
         p = node!(ll, p).prevz_idx;
@@ -553,14 +1478,7 @@ fn is_ear_hashed(
 
     nodemut!(ll, NULL).z = max_z + 1;
     while node!(ll, n).z <= max_z {
-        if earcheck(
-            prev,
-            ear,
-            next,
-            prevref!(ll, n),
-            noderef!(ll, n),
-            nextref!(ll, n),
-        ) {
+        if earcheck(ll, prev, ear, next, n) {
             return false;
         }
         n = node!(ll, n).nextz_idx;
@@ -587,12 +1505,15 @@ fn filter_points(ll: &mut LinkedLists, start: NodeIdx, mut end: NodeIdx) -> Node
     // this loop "wastes" calculations by going over the same points multiple
     // times. however, altering the location of the 'end' node can disrupt
     // the algorithm of other code that calls the filter_points function.
-    loop {
+    let end = loop {
         again = false;
+        let duplicate = equals(noderef!(ll, p), nextref!(ll, p));
         if !node!(ll, p).steiner
-            && (equals(noderef!(ll, p), nextref!(ll, p))
-                || area(prevref!(ll, p), noderef!(ll, p), nextref!(ll, p)) == 0.0)
+            && (duplicate
+                || ll.opts.predicates.area(prevref!(ll, p), noderef!(ll, p), nextref!(ll, p))
+                    == 0.0)
         {
+            ll.note_dropped(p, if duplicate { Dropped::Duplicate } else { Dropped::Collinear });
             ll.remove_node(p);
             end = node!(ll, p).prev_idx;
             p = end;
@@ -606,13 +1527,16 @@ fn filter_points(ll: &mut LinkedLists, start: NodeIdx, mut end: NodeIdx) -> Node
         if !again && p == end {
             break end;
         }
-    }
+    };
+    #[cfg(feature = "check")]
+    check::check_links(ll, "filter_points", end, end);
+    end
 }
 
 // create a circular doubly linked list from polygon points in the
 // specified winding order
-fn linked_list(
-    data: &Vec<f64>,
+fn linked_list<T: Coord>(
+    data: &[T],
     start: usize,
     end: usize,
     clockwise: bool,
@@ -626,51 +1550,52 @@ fn linked_list(
 }
 
 // add new nodes to an existing linked list.
-fn linked_list_add_contour(
+fn linked_list_add_contour<T: Coord>(
     ll: &mut LinkedLists,
-    data: &Vec<f64>,
+    data: &[T],
     start: usize,
     end: usize,
     clockwise: bool,
 ) -> (NodeIdx, NodeIdx) {
-    if start > data.len() || end > data.len() || data.len() == 0 {
+    if start >= end || end > data.len() {
         return (NULL, NULL);
     }
     let mut lastidx = NULL;
     let mut leftmost_idx = NULL;
     let mut contour_minx = std::f64::MAX;
 
-    if clockwise == (signed_area(&data, start, end) > 0.0) {
+    if clockwise == (T::signed_area(data, start, end, ll) > 0.0) {
         for i in (start..end).step_by(DIM) {
-            lastidx = ll.insert_node(i / DIM, data[i], data[i + 1], lastidx);
-            if contour_minx > data[i] {
-                contour_minx = data[i];
+            let x = (data[i].to_f64() - ll.ox) * ll.scale;
+            let y = (data[i + 1].to_f64() - ll.oy) * ll.scale;
+            lastidx = ll.insert_node(i / DIM, x, y, lastidx);
+            if contour_minx > x {
+                contour_minx = x;
                 leftmost_idx = lastidx
             };
-            if ll.usehash {
-                ll.miny = f64::min(data[i + 1], ll.miny);
-                ll.maxx = f64::max(data[i], ll.maxx);
-                ll.maxy = f64::max(data[i + 1], ll.maxy);
-            }
+            ll.miny = f64::min(y, ll.miny);
+            ll.maxx = f64::max(x, ll.maxx);
+            ll.maxy = f64::max(y, ll.maxy);
         }
     } else {
         for i in (start..=(end - DIM)).rev().step_by(DIM) {
-            lastidx = ll.insert_node(i / DIM, data[i], data[i + 1], lastidx);
-            if contour_minx > data[i] {
-                contour_minx = data[i];
+            let x = (data[i].to_f64() - ll.ox) * ll.scale;
+            let y = (data[i + 1].to_f64() - ll.oy) * ll.scale;
+            lastidx = ll.insert_node(i / DIM, x, y, lastidx);
+            if contour_minx > x {
+                contour_minx = x;
                 leftmost_idx = lastidx
             };
-            if ll.usehash {
-                ll.miny = f64::min(data[i + 1], ll.miny);
-                ll.maxx = f64::max(data[i], ll.maxx);
-                ll.maxy = f64::max(data[i + 1], ll.maxy);
-            }
+            ll.miny = f64::min(y, ll.miny);
+            ll.maxx = f64::max(x, ll.maxx);
+            ll.maxy = f64::max(y, ll.maxy);
         }
     }
 
     ll.minx = f64::min(contour_minx, ll.minx);
 
     if equals(noderef!(ll, lastidx), nextref!(ll, lastidx)) {
+        ll.note_dropped(lastidx, Dropped::Duplicate);
         ll.remove_node(lastidx);
         lastidx = noderef!(ll, lastidx).next_idx;
     }
@@ -704,37 +1629,242 @@ fn point_in_triangle(a: &Node, b: &Node, c: &Node, p: &Node) -> bool {
 }
 
 pub fn earcut(data: &Vec<f64>, hole_indices: &Vec<usize>, dims: usize) -> Vec<usize> {
-    let outer_len = match hole_indices.len() {
-        0 => data.len(),
-        _ => hole_indices[0] * DIM,
+    earcut_with_options(data, hole_indices, dims, &EarcutOptions::default())
+        .unwrap_or_default()
+}
+
+// same as earcut(), but with the behavior adjusted by 'options'. errors
+// can only be returned by options that ask for them, like strict mode,
+// or by integer coordinates that are out of range. data can be f64, i32
+// or i64, see Coord
+pub fn earcut_with_options<T: Coord>(
+    data: &Vec<T>,
+    hole_indices: &Vec<usize>,
+    dims: usize,
+    options: &EarcutOptions,
+) -> Result<Vec<usize>, EarcutError> {
+    let mut ll = LinkedLists::new(data.len() / DIM);
+    earcut_ll(&mut ll, data, hole_indices, dims, options)
+}
+
+// same as earcut_with_options(), plus counts of what the algorithm did
+// and the time spent in each phase, for finding out why a polygon is
+// slow or badly triangulated. the stats are returned on errors too
+pub fn earcut_with_stats<T: Coord>(
+    data: &Vec<T>,
+    hole_indices: &Vec<usize>,
+    dims: usize,
+    options: &EarcutOptions,
+) -> (Result<Vec<usize>, EarcutError>, EarcutStats) {
+    let mut ll = LinkedLists::new(data.len() / DIM);
+    ll.timed = true;
+    let result = earcut_ll(&mut ll, data, hole_indices, dims, options);
+    (result, ll.stats)
+}
+
+// same as earcut_with_options(), plus a record of every step the
+// algorithm took, see TraceEvent. trace_json turns it into JSON that the
+// viewer in viz/ can replay
+#[cfg(feature = "trace")]
+pub fn earcut_with_trace<T: Coord>(
+    data: &Vec<T>,
+    hole_indices: &Vec<usize>,
+    dims: usize,
+    options: &EarcutOptions,
+) -> (Result<Vec<usize>, EarcutError>, Vec<TraceEvent>) {
+    let mut ll = LinkedLists::new(data.len() / DIM);
+    ll.trace = Some(Vec::new());
+    let result = earcut_ll(&mut ll, data, hole_indices, dims, options);
+    (result, ll.trace.unwrap_or_default())
+}
+
+// same as earcut_with_options(), plus which input vertices and rings
+// are in no triangle, and why, see Diagnostics. on an error that holds no
+// triangles, every vertex counts as dropped
+pub fn earcut_with_diagnostics<T: Coord>(
+    data: &Vec<T>,
+    hole_indices: &Vec<usize>,
+    dims: usize,
+    options: &EarcutOptions,
+) -> (Result<Vec<usize>, EarcutError>, Diagnostics) {
+    let mut ll = LinkedLists::new(data.len() / DIM);
+    ll.diagnostics = Some(Diagnostics::default());
+    let result = earcut_ll(&mut ll, data, hole_indices, dims, options);
+    let triangles = match &result {
+        Ok(triangles)
+        | Err(EarcutError::OverBudget { triangles })
+        | Err(EarcutError::Cancelled { triangles }) => &triangles[..],
+        Err(_) => &[],
     };
+    let notes = ll.diagnostics.take().unwrap_or_default();
+    let diagnostics = diagnostics::finish(notes, data.len() / DIM, hole_indices, triangles);
+    (result, diagnostics)
+}
 
-    let (mut ll, mut outer_node) = linked_list(data, 0, outer_len, true);
+// earcut_with_options, using the empty linked lists 'll'
+fn earcut_ll<T: Coord>(
+    ll: &mut LinkedLists,
+    data: &Vec<T>,
+    hole_indices: &Vec<usize>,
+    dims: usize,
+    options: &EarcutOptions,
+) -> Result<Vec<usize>, EarcutError> {
+    let snapped = match options.snap {
+        Snap::Weld(epsilon) if epsilon > 0.0 => Some(weld(data, epsilon)),
+        Snap::Grid(cell) if cell > 0.0 => Some(snap_to_grid(data, cell)),
+        _ => None,
+    };
+    let data = snapped.as_ref().unwrap_or(data);
     let mut triangles: Vec<usize> = Vec::with_capacity(data.len() / DIM);
-    if ll.nodes.len() == 1 || DIM != dims {
-        return triangles;
+    if DIM != dims {
+        return Ok(triangles);
+    }
+    // a coordinate without a partner, at the end, is ignored
+    let n = data.len() / DIM;
+    if let Some(i) = data[..n * DIM].iter().position(|c| !c.in_range()) {
+        return Err(EarcutError::OutOfRange { vertex: i / DIM });
     }
+    check_hole_indices(hole_indices, n)?;
+    let outer_len = match hole_indices.len() {
+        0 => n * DIM,
+        _ => hole_indices[0] * DIM,
+    };
 
-    outer_node = eliminate_holes(&mut ll, data, hole_indices, outer_node);
+    ll.usehash = data.len() / DIM >= options.hash_threshold;
+    let (ox, oy, scale) = local_frame(data, options.normalize && !T::INTEGER);
+    ll.ox = ox;
+    ll.oy = oy;
+    ll.scale = scale;
+
+    let started = clock(ll);
+    let (mut outer_node, _) = linked_list_add_contour(ll, data, 0, outer_len, true);
+    ll.stats.build_time += elapsed(started);
+    if ll.nodes.len() == 1 {
+        return Ok(triangles);
+    }
+    trace!(ll, TraceEvent::Ring { ring: ring_indices(ll, outer_node), hole: false });
+    ll.opts = options.clone();
+    if T::INTEGER {
+        ll.opts.predicates = Predicates::Integer;
+    }
+
+    outer_node = eliminate_holes(ll, data, hole_indices, outer_node)?;
+    // a constraint polyline across the polygon cuts it in pieces
+    let pieces = match ll.joins.iter().any(|j| j.split) {
+        true => join_second_ends(ll, outer_node),
+        false => vec![outer_node],
+    };
 
+    let started = clock(ll);
     if ll.usehash {
-        ll.invsize = calc_invsize(ll.minx, ll.miny, ll.maxx, ll.maxy);
-
-        // translate all points so min is 0,0. prevents subtraction inside
-        // zorder. also note invsize does not depend on translation in space
-        // if one were translating in a space with an even spaced grid of points.
-        // floating point space is not evenly spaced, but it is close enough for
-        // this hash algorithm
-        let (mx, my) = (ll.minx, ll.miny);
-        ll.nodes.iter_mut().for_each(|n| n.x -= mx);
-        ll.nodes.iter_mut().for_each(|n| n.y -= my);
-	    earcut_linked_hashed(&mut ll, outer_node, &mut triangles, 0);
-    } else {
-	    earcut_linked_unhashed(&mut ll, outer_node, &mut triangles, 0);
-	}
+        // the local frame starts at 0,0, so zorder needs no subtraction
+        ll.invsize = match T::INTEGER {
+            true => calc_invsize_int(ll.minx, ll.miny, ll.maxx, ll.maxy, options.zorder_bits),
+            false => calc_invsize(ll.minx, ll.miny, ll.maxx, ll.maxy, options.zorder_bits),
+        };
+    }
+    let result = pieces.into_iter().try_for_each(|piece| {
+        if ll.usehash {
+            earcut_linked_hashed(ll, piece, &mut triangles, 0)
+        } else if let Some(objective) = options.optimal.filter(|_| ll.stats.holes == 0) {
+            earcut_optimal(ll, piece, &mut triangles, objective)
+        } else {
+            earcut_linked_unhashed(ll, piece, &mut triangles, 0)
+        }
+    });
+    ll.stats.clip_time = elapsed(started).saturating_sub(ll.stats.zorder_time);
+
+    match result {
+        Ok(()) => {
+            if options.delaunay {
+                ll.stats.flips = delaunay::flip(&data[..n * DIM], hole_indices, &mut triangles);
+            }
+            if options.drop_degenerate {
+                ll.stats.degenerate = quality::drop_degenerate(&data[..n * DIM], &mut triangles);
+            }
+            Ok(triangles)
+        }
+        Err(EarcutError::OverBudget { .. }) => Err(EarcutError::OverBudget { triangles }),
+        Err(EarcutError::Cancelled { .. }) => Err(EarcutError::Cancelled { triangles }),
+        Err(e) => Err(e),
+    }
+}
+
+// hole indices must not decrease, nor point past the last of n vertices
+fn check_hole_indices(hole_indices: &[usize], n: usize) -> Result<(), EarcutError> {
+    let bad_hole = (0..hole_indices.len())
+        .find(|&i| hole_indices[i] > n || (i > 0 && hole_indices[i] < hole_indices[i - 1]));
+    match bad_hole {
+        Some(hole) => Err(EarcutError::BadHoleIndex { hole }),
+        None => Ok(()),
+    }
+}
+
+// give each vertex within epsilon of an earlier, unwelded, vertex the
+// coordinates of that vertex. equals() then sees them as duplicates, and
+// filter_points drops all but one. a grid of epsilon sized cells keeps the
+// neighbor search short
+fn weld<T: Coord>(data: &[T], epsilon: f64) -> Vec<T> {
+    let mut welded = data.to_vec();
+    let mut cells: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+    let cell = |c: f64| (c / epsilon).floor() as i64;
+    let near = |range: i64| range.saturating_sub(1)..=range.saturating_add(1);
+    for i in (0..data.len() - data.len() % DIM).step_by(DIM) {
+        let (x, y) = (data[i].to_f64(), data[i + 1].to_f64());
+        let (cx, cy) = (cell(x), cell(y));
+        let first = near(cx)
+            .flat_map(|gx| near(cy).map(move |gy| (gx, gy)))
+            .filter_map(|key| cells.get(&key))
+            .flatten()
+            .filter(|&&j| {
+                let (dx, dy) = (data[j].to_f64() - x, data[j + 1].to_f64() - y);
+                dx * dx + dy * dy <= epsilon * epsilon
+            })
+            .min();
+        match first {
+            Some(&j) => {
+                welded[i] = data[j];
+                welded[i + 1] = data[j + 1];
+            }
+            None => cells.entry((cx, cy)).or_default().push(i),
+        }
+    }
+    welded
+}
 
+fn snap_to_grid<T: Coord>(data: &[T], cell: f64) -> Vec<T> {
+    data.iter()
+        .map(|c| T::from_f64((c.to_f64() / cell).round() * cell))
+        .collect()
+}
 
-    triangles
+// origin and scale of the local frame the triangulation works in. all
+// geometric tests take differences of coordinates, and the rounding error
+// of a difference is relative to the size of the operands; moving the
+// polygon's bounding box to 0,0 makes that error relative to the size of
+// the polygon instead of its distance from the origin. the shift itself
+// rounds each coordinate by at most half an ulp of the polygon's extent,
+// and is exact for integers. 'normalize' additionally picks a power of
+// two scale that fits the bounding box into the unit square
+fn local_frame<T: Coord>(data: &[T], normalize: bool) -> (f64, f64, f64) {
+    let (mut minx, mut miny) = (f64::MAX, f64::MAX);
+    let (mut maxx, mut maxy) = (f64::MIN, f64::MIN);
+    for c in data.chunks(DIM).filter(|c| c.len() == DIM) {
+        let (x, y) = (c[0].to_f64(), c[1].to_f64());
+        minx = f64::min(x, minx);
+        miny = f64::min(y, miny);
+        maxx = f64::max(x, maxx);
+        maxy = f64::max(y, maxy);
+    }
+    if minx > maxx {
+        return (0.0, 0.0, 1.0);
+    }
+    let size = f64::max(maxx - minx, maxy - miny);
+    let scale = match normalize && size > 0.0 && size.is_finite() {
+        true => 2f64.powi(-(size.log2().ceil() as i32).clamp(-1022, 1023)),
+        false => 1.0,
+    };
+    (minx, miny, scale)
 }
 
 // signed area of a parallelogram
@@ -742,6 +1872,11 @@ fn area(p: &Node, q: &Node, r: &Node) -> f64 {
     (q.y - p.y) * (r.x - q.x) - (q.x - p.x) * (r.y - q.y)
 }
 
+// vertex indices of the ring containing node 'start', in linked list order
+fn ring_indices(ll: &LinkedLists, start: NodeIdx) -> Vec<VertIdx> {
+    ll.iter(start..start).map(|n| n.i).collect()
+}
+
 // check if two points are equal
 fn equals(p1: &Node, p2: &Node) -> bool {
     p1.x == p2.x && p1.y == p2.y
@@ -785,6 +1920,7 @@ fn cure_local_intersections(
 
         if !equals(noderef!(ll, a), noderef!(ll, b))
             && pseudo_intersects(
+                ll.opts.predicates,
                 noderef!(ll, a),
                 noderef!(ll, p),
                 nextref!(ll, p),
@@ -797,10 +1933,17 @@ fn cure_local_intersections(
             triangles.push(noderef!(ll, a).i);
             triangles.push(noderef!(ll, p).i);
             triangles.push(noderef!(ll, b).i);
+            ll.stats.cure_fixes += 1;
+            trace!(ll, TraceEvent::Cure {
+                a: noderef!(ll, a).i,
+                b: noderef!(ll, p).i,
+                c: noderef!(ll, b).i,
+            });
 
             // remove two nodes involved
             ll.remove_node(p);
             let nidx = noderef!(ll, p).next_idx;
+            ll.note_dropped(nidx, Dropped::SelfIntersection);
             ll.remove_node(nidx);
 
             start = noderef!(ll, b).idx;
@@ -815,17 +1958,19 @@ fn cure_local_intersections(
     return p;
 }
 
-// try splitting polygon into two and triangulate them independently
-fn split_earcut(ll: &mut LinkedLists, start_idx: NodeIdx, triangles: &mut Vec<NodeIdx>) {
+// try splitting polygon into two, to be triangulated independently
+fn split_earcut(ll: &mut LinkedLists, start_idx: NodeIdx) -> Result<Step, EarcutError> {
     // look for a valid diagonal that divides the polygon into two
     let mut a = start_idx;
     loop {
         let mut b = next!(ll, a).next_idx;
         while b != noderef!(ll, a).prev_idx {
+            tick(ll)?;
             if noderef!(ll, a).i != noderef!(ll, b).i
                 && is_valid_diagonal(ll, noderef!(ll, a), noderef!(ll, b))
             {
                 // split the polygon in two by the diagonal
+                trace!(ll, TraceEvent::Split { a: noderef!(ll, a).i, b: noderef!(ll, b).i });
                 let mut c = split_bridge_polygon(ll, a, b);
 
                 // filter colinear points around the cuts
@@ -835,26 +1980,207 @@ fn split_earcut(ll: &mut LinkedLists, start_idx: NodeIdx, triangles: &mut Vec<No
                 c = filter_points(ll, c, cn);
 
                 // run earcut on each half
-                earcut_linked_hashed(ll, a, triangles, 0);
-                earcut_linked_hashed(ll, c, triangles, 0);
-                return;
+                ll.stats.splits += 1;
+                return Ok(Step::Split(a, c));
             }
             b = noderef!(ll, b).next_idx;
         }
         a = noderef!(ll, a).next_idx;
         if a == start_idx {
-            break;
+            ll.note_ring_dropped(start_idx, Dropped::Unclipped);
+            break Ok(Step::Done);
         }
     }
 }
 
 // find a bridge between vertices that connects hole with an outer ring
-// and and link it
-fn eliminate_hole(ll: &mut LinkedLists, hole_idx: NodeIdx, outer_node_idx: NodeIdx) {
+// and and link it. returns the outer ring end of the bridge
+fn eliminate_hole(
+    ll: &mut LinkedLists,
+    hole_idx: NodeIdx,
+    outer_node_idx: NodeIdx,
+) -> Result<NodeIdx, EarcutError> {
     let test_idx = find_hole_bridge(ll, hole_idx, outer_node_idx);
+    if test_idx == NULL && ll.opts.strict {
+        return Err(EarcutError::NoHoleBridge {
+            hole: ring_indices(ll, hole_idx),
+            outer: ring_indices(ll, outer_node_idx),
+        });
+    }
+    // like earcut.js, leave out a hole that can't be bridged, rather than
+    // link it to the NULL node
+    if test_idx == NULL {
+        ll.note_ring_dropped(hole_idx, Dropped::NotBridged);
+        return Ok(outer_node_idx);
+    }
+    // the ring of a constraint polyline goes through its points twice,
+    // once on either side. so do the rings it has been bridged into
+    let (hole_idx, test_idx) = match ll.constraints_from != usize::MAX {
+        true => (visit_toward(ll, hole_idx, test_idx), visit_toward(ll, test_idx, hole_idx)),
+        false => (hole_idx, test_idx),
+    };
+    trace!(ll, TraceEvent::Bridge {
+        hole: node!(ll, hole_idx).i,
+        outer: node!(ll, test_idx).i,
+    });
     let b = split_bridge_polygon(ll, test_idx, hole_idx);
+    // the copies of polyline nodes stay, like the nodes: the bridge may go
+    // on straight along a segment
+    if ll.constraints_from != usize::MAX {
+        for (p, copy) in [(hole_idx, b), (test_idx, node!(ll, b).next_idx)] {
+            let (c, prev, next) = (node!(ll, copy), prev!(ll, copy), next!(ll, copy));
+            if node!(ll, p).steiner && !equals(c, prev) && !equals(c, next) {
+                nodemut!(ll, copy).steiner = true;
+            }
+        }
+    }
     let ni = node!(ll, b).next_idx;
-    filter_points(ll, b, ni);
+    let end = filter_points(ll, b, ni);
+    ll.stats.holes += 1;
+    // when the ring was all but filtered away, the bridge may be gone too
+    Ok(if node!(ll, test_idx).removed { end } else { test_idx })
+}
+
+// of the nodes in p's ring at the same place as p, one from which the
+// diagonal to q is locally inside the ring; p itself, if none is
+fn visit_toward(ll: &LinkedLists, p: NodeIdx, q: NodeIdx) -> NodeIdx {
+    let (pn, qn) = (noderef!(ll, p), noderef!(ll, q));
+    if locally_inside(ll, pn, qn) {
+        return p;
+    }
+    ll.iter(p..p)
+        .find(|v| equals(v, pn) && locally_inside(ll, v, qn))
+        .map_or(p, |v| v.idx)
+}
+
+// the first node in use that f is true of, or NULL
+fn find_node<F: Fn(&Node) -> bool>(ll: &LinkedLists, f: F) -> NodeIdx {
+    ll.nodes.iter().skip(1).find(|n| !n.removed && f(n)).map_or(NULL, |n| n.idx)
+}
+
+// put a node into every ring edge a constraint polyline ends on, at the
+// end, then link the polylines into their rings at their first end on
+// one. returns the ends that found no node of a ring to join
+fn join_first_ends(ll: &mut LinkedLists) -> Vec<NodeIdx> {
+    let mut ends = Vec::new();
+    for join in ll.joins.clone() {
+        let end = find_node(ll, |n| n.i == join.end);
+        let on_edge = match join.a == join.b {
+            true => NULL,
+            false => split_edge(ll, join.a, join.b, end),
+        };
+        ends.push((end, on_edge));
+    }
+    let mut unjoined = Vec::new();
+    for (join, (end, on_edge)) in ll.joins.clone().into_iter().zip(ends).filter(|(j, _)| !j.split) {
+        let (e, f) = (node!(ll, end).clone(), node!(ll, find_node(ll, |n| n.i == join.from)).clone());
+        let at = match join.a == join.b {
+            // a ring may go through the vertex more than once
+            true => find_node(ll, |n| n.i < ll.constraints_from && equals(n, &e) && locally_inside(ll, n, &f)),
+            false => on_edge,
+        };
+        if at == NULL {
+            unjoined.push(end);
+            continue;
+        }
+        trace!(ll, TraceEvent::Bridge { hole: e.i, outer: node!(ll, at).i });
+        nodemut!(ll, at).steiner = true;
+        join_at(ll, end, at);
+        ll.stats.holes += 1;
+    }
+    unjoined
+}
+
+// join the second ends of the constraint polylines that have both on
+// rings. each cuts the polygon of 'outer' in two; returns a node of every
+// piece
+fn join_second_ends(ll: &mut LinkedLists, outer: NodeIdx) -> Vec<NodeIdx> {
+    let mut pieces = vec![outer];
+    for join in ll.joins.clone().into_iter().filter(|j| j.split) {
+        let (end, from) = (find_node(ll, |n| n.i == join.end), find_node(ll, |n| n.i == join.from));
+        let (e, f) = (node!(ll, end).clone(), node!(ll, from).clone());
+        // of the visits to the end, u comes along the polyline, and v is
+        // one of the ring's, with the polyline going off inside it
+        let along = |n: &Node| equals(prevref!(ll, n.idx), &f) || equals(nextref!(ll, n.idx), &f);
+        let found = pieces.iter().enumerate().find_map(|(k, &piece)| {
+            let at: Vec<&Node> = ll.iter(piece..piece).filter(|n| equals(n, &e)).collect();
+            let u = at.iter().find(|n| equals(prevref!(ll, n.idx), &f))?;
+            let v = at.iter().find(|n| !along(n) && locally_inside(ll, n, &f))?;
+            Some((k, u.idx, v.idx))
+        });
+        if let Some((k, u, v)) = found {
+            trace!(ll, TraceEvent::Bridge { hole: node!(ll, u).i, outer: node!(ll, v).i });
+            join_at(ll, u, v);
+            pieces[k] = u;
+            pieces.push(v);
+        }
+    }
+    pieces
+}
+
+// put a node at the place of 'end' into the ring edge from vertex a to
+// b, past the nodes put there before that are nearer a. returns it, or
+// NULL if there is no such edge
+fn split_edge(ll: &mut LinkedLists, a: VertIdx, b: VertIdx, end: NodeIdx) -> NodeIdx {
+    let path = edge_nodes(ll, a, b);
+    if path.is_empty() {
+        return NULL;
+    }
+    let (x, y) = (node!(ll, end).x, node!(ll, end).y);
+    let after = {
+        let (ax, ay) = (node!(ll, path[0]).x, node!(ll, path[0]).y);
+        let far = |x: f64, y: f64| (x - ax) * (x - ax) + (y - ay) * (y - ay);
+        let k = (0..path.len() - 1)
+            .rev()
+            .find(|&k| far(node!(ll, path[k]).x, node!(ll, path[k]).y) < far(x, y))
+            .unwrap_or(0);
+        match next!(ll, path[k]).idx == path[k + 1] {
+            true => path[k],
+            false => path[k + 1],
+        }
+    };
+    ll.insert_node(node!(ll, end).i, x, y, after)
+}
+
+// the nodes along the ring edge from vertex a to b: a, those split_edge
+// put into it, and b. empty if there is no such edge
+fn edge_nodes(ll: &LinkedLists, a: VertIdx, b: VertIdx) -> Vec<NodeIdx> {
+    for start in ll.nodes.iter().skip(1).filter(|n| n.i == a && !n.removed) {
+        for forward in [true, false] {
+            let mut path = vec![start.idx];
+            loop {
+                let last = path[path.len() - 1];
+                let p = match forward {
+                    true => next!(ll, last).idx,
+                    false => prev!(ll, last).idx,
+                };
+                path.push(p);
+                if node!(ll, p).i == b {
+                    return path;
+                }
+                if node!(ll, p).i < ll.constraints_from || p == start.idx {
+                    break;
+                }
+            }
+        }
+    }
+    Vec::new()
+}
+
+// link the rings of u and v, at the same place, by a bridge of no length:
+// u goes on to where v went, and v to where u went. two rings become one,
+// and one ring becomes two
+fn join_at(ll: &mut LinkedLists, u: NodeIdx, v: NodeIdx) {
+    let (un, vn) = (next!(ll, u).idx, next!(ll, v).idx);
+    nodemut!(ll, u).next_idx = vn;
+    nodemut!(ll, vn).prev_idx = u;
+    nodemut!(ll, v).next_idx = un;
+    nodemut!(ll, un).prev_idx = v;
+    #[cfg(feature = "check")]
+    {
+        check::check_links(ll, "join_at", u, u);
+        check::check_links(ll, "join_at", v, v);
+    }
 }
 
 // David Eberly's algorithm for finding a bridge between hole and outer polygon
@@ -864,17 +2190,24 @@ fn find_hole_bridge(ll: &LinkedLists, hole: NodeIdx, outer_node: NodeIdx) -> Nod
     let hy = node!(ll, hole).y;
     let mut qx: f64 = std::f64::NEG_INFINITY;
     let mut m: NodeIdx = NULL;
+    let pred = ll.opts.predicates;
 
     // find a segment intersected by a ray from the hole's leftmost
     // point to the left; segment's endpoint with lesser x will be
     // potential connection point
     let calcx =
         |p: &Node| p.x + (hy - p.y) * (next!(ll, p.idx).x - p.x) / (next!(ll, p.idx).y - p.y);
+    // calcx(p) <= hx, where n.y < p.y. in robust mode that is asked as
+    // which side of the segment the hole point is on
+    let left_of_hole = |p: &Node, n: &Node| match pred {
+        Predicates::Fast => calcx(p) <= hx,
+        _ => pred.area(p, n, noderef!(ll, hole)) <= 0.0,
+    };
     for (p, n) in ll
         .iter_pairs(p..outer_node)
         .filter(|(p, n)| hy <= p.y && hy >= n.y)
         .filter(|(p, n)| n.y != p.y)
-        .filter(|(p, _)| calcx(p) <= hx)
+        .filter(|(p, n)| left_of_hole(p, n))
     {
         if qx < calcx(p) {
             qx = calcx(p);
@@ -908,10 +2241,15 @@ fn find_hole_bridge(ll: &LinkedLists, hole: NodeIdx, outer_node: NodeIdx) -> Nod
     let n1 = Node::new(0, x1, hy, 0);
     let n2 = Node::new(0, x2, hy, 0);
 
+    // qx is not an integer, so the integer predicates can't be used here
+    let pred = match pred {
+        Predicates::Integer => Predicates::Robust,
+        _ => pred,
+    };
     let calctan = |p: &Node| (hy - p.y).abs() / (hx - p.x); // tangential
     ll.iter(p..m)
         .filter(|p| hx > p.x && p.x >= mp.x)
-        .filter(|p| point_in_triangle(&n1, &mp, &n2, &p))
+        .filter(|p| pred.point_in_triangle(&n1, &mp, &n2, &p))
         .fold((m, std::f64::MAX / 2.), |(m, tan_min), p| {
             if ((calctan(p) < tan_min) || (calctan(p) == tan_min && p.x > noderef!(ll, m).x))
                 && locally_inside(ll, &p, noderef!(ll, hole))
@@ -965,36 +2303,67 @@ detection for endpoint detection.
     p2 q1
 */
 
-fn pseudo_intersects(p1: &Node, q1: &Node, p2: &Node, q2: &Node) -> bool {
+fn pseudo_intersects(pred: Predicates, p1: &Node, q1: &Node, p2: &Node, q2: &Node) -> bool {
     if (equals(p1, p2) && equals(q1, q2)) || (equals(p1, q2) && equals(q1, p2)) {
         return true;
     }
-    return (area(p1, q1, p2) > 0.0) != (area(p1, q1, q2) > 0.0)
-        && (area(p2, q2, p1) > 0.0) != (area(p2, q2, q1) > 0.0);
+    return (pred.area(p1, q1, p2) > 0.0) != (pred.area(p1, q1, q2) > 0.0)
+        && (pred.area(p2, q2, p1) > 0.0) != (pred.area(p2, q2, q1) > 0.0);
 }
 
 // check if a polygon diagonal intersects any polygon segments
 fn intersects_polygon(ll: &LinkedLists, a: &Node, b: &Node) -> bool {
     ll.iter_pairs(a.idx..a.idx).any(|(p, n)| {
-        p.i != a.i && n.i != a.i && p.i != b.i && n.i != b.i && pseudo_intersects(&p, &n, a, b)
+        p.i != a.i
+            && n.i != a.i
+            && p.i != b.i
+            && n.i != b.i
+            && pseudo_intersects(ll.opts.predicates, &p, &n, a, b)
     })
 }
 
 // check if a polygon diagonal is locally inside the polygon
 fn locally_inside(ll: &LinkedLists, a: &Node, b: &Node) -> bool {
-    match area(prevref!(ll, a.idx), a, nextref!(ll, a.idx)) < 0.0 {
-        true => area(a, b, nextref!(ll, a.idx)) >= 0.0 && area(a, prevref!(ll, a.idx), b) >= 0.0,
-        false => area(a, b, prevref!(ll, a.idx)) < 0.0 || area(a, nextref!(ll, a.idx), b) < 0.0,
+    let pred = ll.opts.predicates;
+    let (prev, next) = (prevref!(ll, a.idx), nextref!(ll, a.idx));
+    match pred.area(prev, a, next) < 0.0 {
+        true => pred.area(a, b, next) >= 0.0 && pred.area(a, prev, b) >= 0.0,
+        false => pred.area(a, b, prev) < 0.0 || pred.area(a, next, b) < 0.0,
     }
 }
 
 // check if the middle point of a polygon diagonal is inside the polygon
 fn middle_inside(ll: &LinkedLists, a: &Node, b: &Node) -> bool {
+    if ll.opts.predicates == Predicates::Integer {
+        return middle_inside_int(ll, a, b);
+    }
     let (mx, my) = ((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+    // is the middle point left of the segment's crossing of y=my
+    let left_of = |p: &Node, n: &Node| match ll.opts.predicates {
+        Predicates::Robust => {
+            let o = robust::orient2d(p.x, p.y, n.x, n.y, mx, my);
+            (n.y > p.y && o > 0.0) || (n.y < p.y && o < 0.0)
+        }
+        _ => (mx) < ((n.x - p.x) * (my - p.y) / (n.y - p.y) + p.x),
+    };
     ll.iter_pairs(a.idx..a.idx)
         .filter(|(p, n)| (p.y > my) != (n.y > my))
         .filter(|(p, n)| n.y != p.y)
-        .filter(|(p, n)| (mx) < ((n.x - p.x) * (my - p.y) / (n.y - p.y) + p.x))
+        .filter(|(p, n)| left_of(p, n))
+        .fold(false, |inside, _| !inside)
+}
+
+// middle_inside for integer coordinates. all coordinates are doubled,
+// so the middle point is an integer too
+fn middle_inside_int(ll: &LinkedLists, a: &Node, b: &Node) -> bool {
+    let (mx, my) = (a.x as i64 + b.x as i64, a.y as i64 + b.y as i64);
+    ll.iter_pairs(a.idx..a.idx)
+        .map(|(p, n)| (2 * p.x as i64, 2 * p.y as i64, 2 * n.x as i64, 2 * n.y as i64))
+        .filter(|&(_, py, _, ny)| (py > my) != (ny > my))
+        .filter(|&(px, py, nx, ny)| {
+            let o = robust::orient2d_int(px, py, nx, ny, mx, my);
+            (ny > py && o > 0) || (ny < py && o < 0)
+        })
         .fold(false, |inside, _| !inside)
 }
 
@@ -1103,6 +2472,12 @@ fn split_bridge_polygon(ll: &mut LinkedLists, a: NodeIdx, b: NodeIdx) -> NodeIdx
 
     ll.nodes.push(c);
     ll.nodes.push(d);
+    ll.stats.bridge_nodes += 2;
+    #[cfg(feature = "check")]
+    {
+        check::check_links(ll, "split_bridge_polygon", a, a);
+        check::check_links(ll, "split_bridge_polygon", didx, didx);
+    }
     return didx;
 }
 
@@ -1139,12 +2514,34 @@ pub fn deviation(
     }
 }
 
-fn signed_area(data: &Vec<f64>, start: usize, end: usize) -> f64 {
+fn signed_area(data: &[f64], start: usize, end: usize) -> f64 {
+    signed_area_local(data, start, end, 0.0, 0.0, 1.0)
+}
+
+// signed_area of the ring moved by -ox,-oy and scaled, see local_frame
+fn signed_area_local(data: &[f64], start: usize, end: usize, ox: f64, oy: f64, scale: f64) -> f64 {
+    let x = |i: usize| (data[i] - ox) * scale;
+    let y = |i: usize| (data[i + 1] - oy) * scale;
     let i = (start..end).step_by(DIM);
     let j = (start..end).cycle().skip((end - DIM) - start).step_by(DIM);
-    i.zip(j).fold(0., |s, (i, j)| {
-        s + (data[j] - data[i]) * (data[i + 1] + data[j + 1])
-    })
+    i.zip(j).fold(0., |s, (i, j)| s + (x(j) - x(i)) * (y(i) + y(j)))
+}
+
+// signed_area for integer coordinates, summed exactly. falls back to f64
+// if the sum overflows, which takes millions of huge coordinates
+fn signed_area_exact<T: Copy + Into<i128>>(data: &[T], start: usize, end: usize) -> f64 {
+    let v = |i: usize| data[i].into();
+    let i = (start..end).step_by(DIM);
+    let j = (start..end).cycle().skip((end - DIM) - start).step_by(DIM);
+    match i.zip(j).try_fold(0i128, |s, (i, j)| {
+        s.checked_add((v(j) - v(i)) * (v(i + 1) + v(j + 1)))
+    }) {
+        Some(s) => s as f64,
+        None => {
+            let fdata: Vec<f64> = (start..end).map(|i| v(i) as f64).collect();
+            signed_area(&fdata, 0, end - start)
+        }
+    }
 }
 
 // turn a polygon in a multi-dimensional array form (e.g. as in GeoJSON)
@@ -1157,13 +2554,13 @@ pub fn flatten(data: &Vec<Vec<Vec<f64>>>) -> (Vec<f64>, Vec<usize>, usize) {
             .flatten()
             .collect::<Vec<f64>>(), // flat data
         data.iter()
-            .take(data.len() - 1)
+            .take(data.len().saturating_sub(1))
             .scan(0, |holeidx, v| {
                 *holeidx += v.len();
                 Some(*holeidx)
             })
             .collect::<Vec<usize>>(), // hole indexes
-        data[0][0].len(), // dimensions
+        data.iter().flatten().next().map_or(DIM, |p| p.len()), // dimensions
     )
 }
 
@@ -1211,9 +2608,28 @@ fn dump(ll: &LinkedLists) -> String {
     return s;
 }
 
+fn cycle_len(ll: &LinkedLists, p: NodeIdx) -> usize {
+    if p >= ll.nodes.len() {
+        return 0;
+    }
+    let end = noderef!(ll, p).prev_idx;
+    let mut i = p;
+    let mut count = 1;
+    loop {
+        i = noderef!(ll, i).next_idx;
+        count += 1;
+        if i == end {
+            break count;
+        }
+        if count > ll.nodes.len() {
+            break count;
+        }
+    }
+}
+
 fn cycle_dump(ll: &LinkedLists, p: NodeIdx) -> String {
     let mut s = format!("cycle from {}, ", p);
-    s.push_str(&format!(" len {}, idxs:", 0)); //cycle_len(&ll, p)));
+    s.push_str(&format!(" len {}, idxs:", cycle_len(ll, p)));
     let mut i = p;
     let end = i;
     let mut count = 0;
@@ -1313,25 +2729,6 @@ mod tests {
         return s;
     }
 
-    fn cycle_len(ll: &LinkedLists, p: NodeIdx) -> usize {
-        if p >= ll.nodes.len() {
-            return 0;
-        }
-        let end = noderef!(ll, p).prev_idx;
-        let mut i = p;
-        let mut count = 1;
-        loop {
-            i = noderef!(ll, i).next_idx;
-            count += 1;
-            if i == end {
-                break count;
-            }
-            if count > ll.nodes.len() {
-                break count;
-            }
-        }
-    }
-
     // https://www.cs.hmc.edu/~geoff/classes/hmc.cs070.200101/homework10/hashfuncs.$
     // https://stackoverflow.com/questions/1908492/unsigned-integer-in-javascript
     fn horsh(mut h: u32, n: u32) -> u32 {
@@ -1535,19 +2932,19 @@ mod tests {
         let m = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
         let (mut ll, _) = linked_list(&m, 0, m.len(), true);
         let (mut tris, pass) = (Vec::new(), 0);
-        earcut_linked_hashed(&mut ll, 1, &mut tris, pass);
+        earcut_linked_hashed(&mut ll, 1, &mut tris, pass).unwrap();
         assert!(tris.len() == 6);
 
         let m = vec![0.0, 0.0, 0.5, 0.5, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
         let (mut ll, _) = linked_list(&m, 0, m.len(), true);
         let (mut tris, pass) = (Vec::new(), 0);
-        earcut_linked_unhashed(&mut ll, 1, &mut tris, pass);
+        earcut_linked_unhashed(&mut ll, 1, &mut tris, pass).unwrap();
         assert!(tris.len() == 9);
 
         let m = vec![0.0, 0.0, 0.5, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
         let (mut ll, _) = linked_list(&m, 0, m.len(), true);
         let (mut tris, pass) = (Vec::new(), 0);
-        earcut_linked_hashed(&mut ll, 1, &mut tris, pass);
+        earcut_linked_hashed(&mut ll, 1, &mut tris, pass).unwrap();
         assert!(tris.len() == 9);
     }
 
@@ -1614,6 +3011,7 @@ mod tests {
             ($ok:expr,$a:expr,$b:expr,$c:expr,$d:expr) => {
                 assert!(
                     $ok == pseudo_intersects(
+                        Predicates::Fast,
                         &ll.nodes[$a],
                         &ll.nodes[$b],
                         &ll.nodes[$c],
@@ -1641,10 +3039,11 @@ mod tests {
 
         let m = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.1, 0.1, 0.9, 1.0, 0.0, 1.0];
         let (ll, _) = linked_list(&m, 0, m.len(), true);
-        assert!(false == pseudo_intersects(&ll.nodes[4], &ll.nodes[5], &ll.nodes[1], &ll.nodes[3]));
+        let (n1, n3, n4, n5) = (&ll.nodes[1], &ll.nodes[3], &ll.nodes[4], &ll.nodes[5]);
+        assert!(false == pseudo_intersects(Predicates::Fast, n4, n5, n1, n3));
 
         // special case
-        assert!(true == pseudo_intersects(&ll.nodes[4], &ll.nodes[5], &ll.nodes[3], &ll.nodes[1]));
+        assert!(true == pseudo_intersects(Predicates::Fast, n4, n5, n3, n1));
     }
 
     #[test]
@@ -1722,7 +3121,7 @@ mod tests {
         linked_list_add_contour(&mut ll, &body, holestart, holeend, false);
         assert!(cycle_len(&ll, 1) == 4);
         assert!(cycle_len(&ll, 5) == 4);
-        eliminate_hole(&mut ll, holestart / DIM + 1, 1);
+        eliminate_hole(&mut ll, holestart / DIM + 1, 1).unwrap();
         println!("{}", dump(&ll));
         println!("{}", cycle_len(&ll, 1));
         println!("{}", cycle_len(&ll, 7));
@@ -1737,7 +3136,7 @@ mod tests {
         assert!(cycle_len(&ll, 1) == 10);
         assert!(cycle_len(&ll, 5) == 10);
         assert!(cycle_len(&ll, 11) == 4);
-        eliminate_hole(&mut ll, 11, 2);
+        eliminate_hole(&mut ll, 11, 2).unwrap();
         assert!(!cycle_len(&ll, 1) != 10);
         assert!(!cycle_len(&ll, 1) != 10);
         assert!(!cycle_len(&ll, 5) != 10);
@@ -1748,6 +3147,32 @@ mod tests {
         assert!(cycle_len(&ll, 15) == 16);
     }
 
+    #[test]
+    fn test_eliminate_hole_no_bridge() {
+        // the hole is left of the outer ring, so nothing is found to the
+        // left of it to bridge to
+        let data = vec![10.0, 0.0, 20.0, 0.0, 20.0, 10.0, 10.0, 10.0, 1.0, 1.0, 2.0, 1.0, 2.0, 2.0];
+        let (mut ll, outer) = linked_list(&data, 0, 8, true);
+        let (hole, _) = linked_list_add_contour(&mut ll, &data, 8, data.len(), false);
+        assert!(find_hole_bridge(&ll, hole, outer) == NULL);
+        assert!(eliminate_hole(&mut ll, hole, outer) == Ok(outer));
+        assert!(cycle_len(&ll, outer) == 4 && cycle_len(&ll, hole) == 3);
+        assert!(ll.stats.holes == 0);
+    }
+
+    #[test]
+    fn test_linked_list_add_contour_empty() {
+        let data = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0];
+        let (mut ll, _) = linked_list(&data, 0, data.len(), true);
+        for &(start, end) in &[(0, 0), (6, 6), (4, 2), (6, 8)] {
+            for &clockwise in &[true, false] {
+                let r = linked_list_add_contour(&mut ll, &data, start, end, clockwise);
+                assert!(r == (NULL, NULL));
+            }
+        }
+        assert!(ll.nodes.len() == 4 && cycle_len(&ll, 1) == 3);
+    }
+
     #[test]
     fn test_cycle_len() {
         let mut body = vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.1, 0.1];
@@ -1798,7 +3223,7 @@ mod tests {
     fn test_eliminate_holes() {
         let mut hole_indices: Vec<usize> = Vec::new();
         let mut body = vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0];
-        let (mut ll, _) = linked_list(&body, 0, body.len(), true);
+        let (mut ll, outer) = linked_list(&body, 0, body.len(), true);
         let hole1 = vec![0.1, 0.1, 0.9, 0.1, 0.9, 0.9, 0.1, 0.9];
         let hole2 = vec![0.2, 0.2, 0.8, 0.2, 0.8, 0.8, 0.2, 0.8];
         hole_indices.push(body.len() / DIM);
@@ -1806,7 +3231,8 @@ mod tests {
         body.extend(hole1);
         body.extend(hole2);
 
-        eliminate_holes(&mut ll, &body, &hole_indices, 0);
+        eliminate_holes(&mut ll, &body, &hole_indices, outer).unwrap();
+        assert!(cycle_len(&ll, outer) == 4 + 2 * (4 + 2));
     }
 
     #[test]
@@ -1839,7 +3265,13 @@ mod tests {
         let (mut ll, _) = linked_list(&m, 0, m.len(), true);
         let start = 1;
         let mut triangles: Vec<usize> = Vec::new();
-        split_earcut(&mut ll, start, &mut triangles);
+        match split_earcut(&mut ll, start).unwrap() {
+            Step::Split(a, c) => {
+                earcut_linked_hashed(&mut ll, a, &mut triangles, 0).unwrap();
+                earcut_linked_hashed(&mut ll, c, &mut triangles, 0).unwrap();
+            }
+            _ => panic!("square not split"),
+        }
         assert!(triangles.len() == 6);
         assert!(ll.nodes.len() == 7);
 
@@ -1849,8 +3281,7 @@ mod tests {
         ];
         let (mut ll, _) = linked_list(&m, 0, m.len(), true);
         let start = 1;
-        let mut triangles: Vec<usize> = Vec::new();
-        split_earcut(&mut ll, start, &mut triangles);
+        split_earcut(&mut ll, start).unwrap();
         assert!(ll.nodes.len() == 13);
     }
 
@@ -1883,6 +3314,9 @@ mod tests {
         assert!(hole_indices.len() == 2);
         assert!(hole_indices[0] == 4);
         assert!(hole_indices[1] == 8);
+
+        assert!(flatten(&vec![]) == (vec![], vec![], DIM));
+        assert!(flatten(&vec![vec![], vec![vec![1.0, 2.0, 3.0]]]) == (vec![1.0, 2.0, 3.0], vec![0], 3));
     }
 
     #[test]
@@ -1902,4 +3336,116 @@ mod tests {
         let triangles = earcut(&coords, &hole_indices, DIM);
         assert!(triangles.len() > 4);
     }
+
+    #[test]
+    fn test_robust_area() {
+        // nearly collinear points. plain f64 math gives answers that change
+        // with the order of the points, the robust ones never do
+        let ulp = f64::EPSILON / 2.0;
+        let (b, c) = (Node::new(1, 12.0, 12.0, 2), Node::new(2, 24.0, 24.0, 3));
+        let mut fast_inconsistent = 0;
+        for i in 0..32 {
+            for j in 0..32 {
+                let a = Node::new(0, 0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp, 1);
+                let signs = |pred: Predicates| {
+                    let s = |x: f64| (x > 0.0) as i32 - (x < 0.0) as i32;
+                    (
+                        s(pred.area(&a, &b, &c)),
+                        s(pred.area(&b, &c, &a)),
+                        s(pred.area(&c, &a, &b)),
+                    )
+                };
+                let (r1, r2, r3) = signs(Predicates::Robust);
+                assert!(r1 == r2 && r2 == r3);
+                let (f1, f2, f3) = signs(Predicates::Fast);
+                if f1 != f2 || f2 != f3 {
+                    fast_inconsistent += 1;
+                }
+            }
+        }
+        assert!(fast_inconsistent > 0);
+    }
+
+    #[test]
+    fn test_calc_invsize() {
+        assert!(calc_invsize(0.0, 0.0, 100.0, 50.0, 15) == 327.67);
+        assert!(calc_invsize(0.0, 0.0, 100.0, 50.0, 4) == 0.15);
+        assert!(calc_invsize(5.0, 5.0, 5.0, 5.0, 15) == 0.0);
+    }
+
+    #[test]
+    fn test_calc_invsize_int() {
+        assert!(calc_invsize_int(0.0, 0.0, 100.0, 50.0, 15) == 1.0);
+        assert!(calc_invsize_int(0.0, 0.0, 32767.0, 0.0, 15) == 1.0);
+        assert!(calc_invsize_int(-1.0, 0.0, 32767.0, 0.0, 15) == 0.5);
+        assert!(calc_invsize_int(0.0, 0.0, 0.0, 65536.0 * 4.0, 15) == 0.0625);
+        assert!(calc_invsize_int(5.0, 5.0, 5.0, 5.0, 15) == 0.0);
+    }
+
+    #[test]
+    fn test_weld() {
+        // no chaining: the third point is near the second, but the second
+        // was already welded to the first
+        let data = vec![0.0, 0.0, 0.6, 0.0, 1.2, 0.0, 5.0, 5.0, 5.0, 5.9];
+        let welded = weld(&data, 1.0);
+        assert!(welded == vec![0.0, 0.0, 0.0, 0.0, 1.2, 0.0, 5.0, 5.0, 5.0, 5.0]);
+        let data = vec![-3, 7, -2, 7, 100, 100];
+        assert!(weld(&data, 1.0) == vec![-3, 7, -3, 7, 100, 100]);
+    }
+
+    #[test]
+    fn test_snap_to_grid() {
+        let data = vec![0.26, -0.74, 1.0, 0.1];
+        assert!(snap_to_grid(&data, 0.5) == vec![0.5, -0.5, 1.0, 0.0]);
+        assert!(snap_to_grid(&[7i32, -7, 5], 4.0) == vec![8, -8, 4]);
+    }
+
+    #[test]
+    fn test_local_frame() {
+        let data = vec![-3.0, 10.0, 5.0, 12.0, 1.0, 14.0];
+        assert!(local_frame(&data, false) == (-3.0, 10.0, 1.0));
+        assert!(local_frame(&data, true) == (-3.0, 10.0, 0.125));
+        assert!(local_frame(&[1e300, 0.0, 3e300, 1e300], true).2 == 2f64.powi(-998));
+        assert!(local_frame(&[2.0, 2.0], true) == (2.0, 2.0, 1.0));
+        assert!(local_frame(&Vec::<f64>::new(), true) == (0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_signed_area_exact() {
+        let data = vec![0, 0, 0, 1, 1, 1, 1, 0];
+        let fdata: Vec<f64> = data.iter().map(|&c| c as f64).collect();
+        assert!(signed_area_exact(&data, 0, 8) == signed_area(&fdata, 0, 8));
+        // a sliver far too thin for f64 to see
+        let big = 1i64 << 52;
+        let data = vec![-big, -big, big, big, big - 1, big];
+        assert!(signed_area_exact(&data, 0, 6) > 0.0);
+    }
+
+    #[test]
+    fn test_middle_inside_int() {
+        let m = [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.9, 0.1];
+        let m: Vec<f64> = m.iter().map(|c| c * 10.0).collect();
+        let (mut ll, _) = linked_list(&m, 0, m.len(), true);
+        ll.opts.predicates = Predicates::Integer;
+        assert!(!middle_inside(&ll, noderef!(ll, 1), noderef!(ll, 3)));
+        assert!(middle_inside(&ll, noderef!(ll, 2), noderef!(ll, 4)));
+    }
+
+    #[test]
+    fn test_strict_filtered() {
+        // collinear and duplicate points do not make strict mode fail
+        let m = vec![0.0, 0.0, 0.5, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 1.0];
+        let (mut ll, _) = linked_list(&m, 0, m.len(), true);
+        ll.opts = EarcutOptions::new().strict(true);
+        let mut tris = Vec::new();
+        earcut_linked_unhashed(&mut ll, 1, &mut tris, 0).unwrap();
+        assert!(tris.len() == 9);
+
+        let m = vec![0.0, 0.0, 1.0, 1.0, 1.0, 0.0, 0.0, 1.0];
+        let (mut ll, _) = linked_list(&m, 0, m.len(), true);
+        ll.opts = EarcutOptions::new().strict(true);
+        let mut tris = Vec::new();
+        let r = earcut_linked_unhashed(&mut ll, 1, &mut tris, 0);
+        assert!(r.is_err());
+    }
 }
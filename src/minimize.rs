@@ -0,0 +1,169 @@
+// shrink a polygon that earcut gets wrong down to something small enough
+// to put in a bug report, by delta debugging: take away rings, then
+// vertices, in ever smaller chunks, for as long as the polygon still
+// fails. the polygons are in the format of tests/fixtures, the outer
+// ring and then the holes, each a list of points
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use super::{flatten, EarcutError};
+
+// the smallest part of 'rings' found that still fails, or None if 'rings'
+// doesn't fail to begin with. 'failing' gets the output of flatten(), and
+// can be anything, like a deviation over some threshold or verify() not
+// being valid. a panic counts as failing too, though the default panic
+// hook still prints it each time. so that the result is still a polygon,
+// the outer ring keeps at least three points, and holes either three or
+// one, a steiner point
+pub fn minimize<F>(rings: &[Vec<Vec<f64>>], mut failing: F) -> Option<Vec<Vec<Vec<f64>>>>
+where
+    F: FnMut(&Vec<f64>, &Vec<usize>, usize) -> bool,
+{
+    let mut run = |rings: &Vec<Vec<Vec<f64>>>| {
+        let (data, holes, dims) = flatten(rings);
+        catch_unwind(AssertUnwindSafe(|| failing(&data, &holes, dims))).unwrap_or(true)
+    };
+    let mut current = rings.to_vec();
+    if current.is_empty() || !run(&current) {
+        return None;
+    }
+    let mut fails = |rings: &Vec<Vec<Vec<f64>>>| {
+        !rings.is_empty() && rings[0].len() >= 3 && rings.iter().all(|r| r.len() != 2) && run(rings)
+    };
+    loop {
+        let size = current.len() + current.iter().map(|r| r.len()).sum::<usize>();
+        let keep = ddmin((0..current.len()).collect(), &mut |keep: &[usize]| {
+            fails(&keep.iter().map(|&r| current[r].clone()).collect())
+        });
+        current = keep.iter().map(|&r| current[r].clone()).collect();
+
+        let points = (0..current.len()).flat_map(|r| (0..current[r].len()).map(move |i| (r, i)));
+        let keep = ddmin(points.collect(), &mut |keep: &[(usize, usize)]| {
+            fails(&pick_points(&current, keep))
+        });
+        current = pick_points(&current, &keep);
+        if current.len() + current.iter().map(|r| r.len()).sum::<usize>() == size {
+            return Some(current);
+        }
+    }
+}
+
+// the rings as a fixture file, one ring per line, like
+// tests/fixtures/steiner.json. JSON has no NaN, it is written as null,
+// which the fixture loaders read back as NaN. there is nothing to write
+// for infinity; an infinite coordinate gives EarcutError::OutOfRange, the
+// vertex counted through the rings in order
+pub fn fixture_json(rings: &[Vec<Vec<f64>>]) -> Result<String, EarcutError> {
+    if let Some(vertex) = rings.iter().flatten().position(|p| p.iter().any(|c| c.is_infinite())) {
+        return Err(EarcutError::OutOfRange { vertex });
+    }
+    let lines: Vec<String> = rings
+        .iter()
+        .map(|ring| {
+            let points: Vec<String> = ring
+                .iter()
+                .map(|p| {
+                    let coords: Vec<String> = p
+                        .iter()
+                        .map(|c| if c.is_nan() { String::from("null") } else { c.to_string() })
+                        .collect();
+                    format!("[{}]", coords.join(","))
+                })
+                .collect();
+            format!("[{}]", points.join(","))
+        })
+        .collect();
+    Ok(format!("[\n{}\n]\n", lines.join(",\n")))
+}
+
+// the points of 'rings' at (ring, index) in 'keep', in order. rings with
+// no points left are left out
+fn pick_points(rings: &[Vec<Vec<f64>>], keep: &[(usize, usize)]) -> Vec<Vec<Vec<f64>>> {
+    let mut picked: Vec<Vec<Vec<f64>>> = Vec::new();
+    let mut last = None;
+    for &(r, i) in keep {
+        if last != Some(r) {
+            picked.push(Vec::new());
+            last = Some(r);
+        }
+        if let Some(ring) = picked.last_mut() {
+            ring.push(rings[r][i].clone());
+        }
+    }
+    picked
+}
+
+// Zeller's delta debugging, taking away one of n chunks at a time: a
+// subset of 'items' that fails, from which no single item can be taken
+// away without it passing
+fn ddmin<T: Clone>(mut items: Vec<T>, fails: &mut dyn FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut n = 2;
+    while items.len() >= 2 {
+        let chunk = items.len().div_ceil(n);
+        let mut reduced = false;
+        for start in (0..items.len()).step_by(chunk) {
+            let end = (start + chunk).min(items.len());
+            let rest: Vec<T> = items[..start].iter().chain(&items[end..]).cloned().collect();
+            if fails(&rest) {
+                items = rest;
+                n = (n - 1).max(2);
+                reduced = true;
+                break;
+            }
+        }
+        if !reduced {
+            if n >= items.len() {
+                break;
+            }
+            n = (2 * n).min(items.len());
+        }
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ddmin() {
+        let fails = &mut |items: &[usize]| items.contains(&3) && items.contains(&7);
+        assert!(ddmin((0..20).collect(), fails) == vec![3, 7]);
+        let fails = &mut |items: &[usize]| items.iter().sum::<usize>() >= 10;
+        assert!(ddmin((0..6).collect(), fails).iter().sum::<usize>() >= 10);
+    }
+
+    #[test]
+    fn test_minimize() {
+        let square = |x: f64, y: f64, w: f64| {
+            vec![vec![x, y], vec![x + w, y], vec![x + w, y + w], vec![x, y + w]]
+        };
+        let rings = vec![square(0.0, 0.0, 10.0), square(1.0, 1.0, 1.0), square(5.0, 5.0, 2.0)];
+        // fails whenever there is a hole with a vertex at 5,5
+        let minimal = minimize(&rings, |data, holes, _| {
+            holes.iter().any(|&h| data[h * 2..].chunks(2).any(|p| p == [5.0, 5.0]))
+        });
+        // any three points will do for the outer ring
+        let minimal = minimal.unwrap();
+        assert!(minimal.len() == 2 && minimal[0].len() == 3);
+        assert!(minimal[1] == vec![vec![5.0, 5.0]]);
+        assert!(minimize(&rings, |_, _, _| false).is_none());
+        // a panic counts as failing
+        let minimal = minimize(&rings, |data, _, _| {
+            assert!(!data.contains(&7.0));
+            false
+        });
+        let minimal = minimal.unwrap();
+        assert!(minimal.len() == 1 && minimal[0].len() == 3);
+        assert!(minimal[0].iter().any(|p| p.contains(&7.0)));
+    }
+
+    #[test]
+    fn test_fixture_json() {
+        let rings = vec![vec![vec![0.0, 0.0], vec![100.0, 0.5]], vec![vec![-50.0, 50.0]]];
+        assert!(fixture_json(&rings) == Ok(String::from("[\n[[0,0],[100,0.5]],\n[[-50,50]]\n]\n")));
+        let rings = vec![vec![vec![0.0, f64::NAN]], vec![vec![1.0, 2.0], vec![-f64::INFINITY, 0.0]]];
+        assert!(fixture_json(&rings[..1]) == Ok(String::from("[\n[[0,null]]\n]\n")));
+        assert!(fixture_json(&rings) == Err(EarcutError::OutOfRange { vertex: 2 }));
+    }
+}
@@ -0,0 +1,226 @@
+// optimal triangulations of small polygons, see EarcutOptions::optimal.
+// in a triangulation of a ring of m nodes, 0 .. m-1, the edge m-1, 0 is
+// in one triangle, whose third corner k splits the rest in two: the
+// chain 0 .. k closed by the diagonal 0-k, and the chain k .. m-1. the
+// best triangulation of every chain i .. j follows from the best of the
+// shorter chains inside it, which makes O(m³) steps, after O(m²)
+// diagonals are tested for O(m) each. that is why it is only done on the
+// unhashed path, for polygons below EarcutOptions::hash_threshold, and
+// never for rings of more than MAX_NODES, whatever the threshold is
+//
+// only simple rings are done: no holes, no vertex twice, no edges that
+// cross or touch. a hole's bridge, or a ring that touches itself, has
+// diagonals that are valid on one side only, and everything else falls
+// back to ear clipping
+
+use super::{equals, is_valid_diagonal, pseudo_intersects, tick, EarcutError, LinkedLists, Node, NodeIdx};
+
+// the most nodes a ring may have to be triangulated here: 50 nodes take
+// about 20000 steps, 500 would take 20 million
+const MAX_NODES: usize = 50;
+
+// what EarcutOptions::optimal makes best
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    // the smallest angle of any triangle is as large as can be
+    MaxMinAngle,
+    // the largest angle of any triangle is as small as can be
+    MinMaxAngle,
+    // the sum of the edge lengths is as small as can be, the minimum
+    // weight triangulation
+    MinWeight,
+}
+
+impl Objective {
+    // what a triangle adds, to be made as small as can be
+    fn cost(self, a: &Node, b: &Node, c: &Node) -> f64 {
+        let (mut min, mut max) = (f64::INFINITY, 0f64);
+        for (p, q, r) in [(a, b, c), (b, c, a), (c, a, b)] {
+            let (ux, uy) = (q.x - p.x, q.y - p.y);
+            let (vx, vy) = (r.x - p.x, r.y - p.y);
+            let angle = (ux * vy - uy * vx).abs().atan2(ux * vx + uy * vy);
+            min = min.min(angle);
+            max = max.max(angle);
+        }
+        match self {
+            Objective::MaxMinAngle => -min,
+            Objective::MinMaxAngle => max,
+            // the ring edges are in every triangulation, so the sum of
+            // the perimeters is smallest where the diagonals are
+            Objective::MinWeight => [(a, b), (b, c), (c, a)]
+                .iter()
+                .map(|(p, q)| (q.x - p.x).hypot(q.y - p.y))
+                .sum(),
+        }
+    }
+
+    // the cost of two triangulations side by side
+    fn add(self, a: f64, b: f64) -> f64 {
+        match self {
+            Objective::MinWeight => a + b,
+            _ => a.max(b),
+        }
+    }
+
+    // the cost of no triangles
+    fn none(self) -> f64 {
+        match self {
+            Objective::MinWeight => 0.0,
+            _ => f64::NEG_INFINITY,
+        }
+    }
+}
+
+// the best triangulation of the ring at 'start', as prev, ear, next node
+// triples in an order they can be clipped in, each an ear of what the
+// ones before it leave. None if the ring has more than MAX_NODES, isn't
+// simple, or has no triangulation without triangles of zero area
+pub(crate) fn triangulate(
+    ll: &mut LinkedLists,
+    start: NodeIdx,
+    objective: Objective,
+) -> Result<Option<Vec<[NodeIdx; 3]>>, EarcutError> {
+    let ring: Vec<NodeIdx> = ll.iter(start..start).map(|n| n.idx).collect();
+    let m = ring.len();
+    if !(3..=MAX_NODES).contains(&m) || !simple(ll, &ring) {
+        return Ok(None);
+    }
+
+    // which chains can be closed: ring edges, and valid diagonals that
+    // don't pass through a vertex
+    let mut closes = vec![false; m * m];
+    for i in 0..m {
+        for j in i + 1..m {
+            closes[i * m + j] = match j == i + 1 || (i == 0 && j == m - 1) {
+                true => true,
+                false => {
+                    tick(ll)?;
+                    let (a, b) = (&ll.nodes[ring[i]], &ll.nodes[ring[j]]);
+                    is_valid_diagonal(ll, a, b) && !ring.iter().any(|&p| on_segment(ll, a, b, &ll.nodes[p]))
+                }
+            };
+        }
+    }
+
+    // the cost of the best triangulation of each chain, and the third
+    // corner of its triangle on the closing edge
+    let mut cost = vec![f64::INFINITY; m * m];
+    let mut corner = vec![0; m * m];
+    for i in 0..m - 1 {
+        cost[i * m + i + 1] = objective.none();
+    }
+    let pred = ll.opts.predicates;
+    for len in 2..m {
+        for i in 0..m - len {
+            let j = i + len;
+            if !closes[i * m + j] {
+                continue;
+            }
+            let (a, c) = (&ll.nodes[ring[i]], &ll.nodes[ring[j]]);
+            for k in i + 1..j {
+                let (left, right) = (cost[i * m + k], cost[k * m + j]);
+                let b = &ll.nodes[ring[k]];
+                // turning the way an ear does
+                if left == f64::INFINITY || right == f64::INFINITY || pred.area(a, b, c) >= 0.0 {
+                    continue;
+                }
+                let total = objective.add(objective.add(left, right), objective.cost(a, b, c));
+                if total < cost[i * m + j] {
+                    cost[i * m + j] = total;
+                    corner[i * m + j] = k;
+                }
+            }
+        }
+    }
+    if cost[m - 1] == f64::INFINITY {
+        return Ok(None);
+    }
+
+    // each chain's triangles before the triangle that closes it
+    let mut ears = Vec::with_capacity(m - 2);
+    let mut stack = vec![(0, m - 1, false)];
+    while let Some((i, j, done)) = stack.pop() {
+        if j - i < 2 {
+            continue;
+        }
+        let k = corner[i * m + j];
+        match done {
+            true => ears.push([ring[i], ring[k], ring[j]]),
+            false => {
+                stack.push((i, j, true));
+                stack.push((k, j, false));
+                stack.push((i, k, false));
+            }
+        }
+    }
+    Ok(Some(ears))
+}
+
+// no two nodes at the same place, and no edge crossing or touching
+// another, other than its neighbors at their shared end
+fn simple(ll: &LinkedLists, ring: &[NodeIdx]) -> bool {
+    let m = ring.len();
+    let node = |i: usize| &ll.nodes[ring[i % m]];
+    (0..m).all(|i| {
+        (i + 1..m).all(|j| {
+            let (p, q, r, s) = (node(i), node(i + 1), node(j), node(j + 1));
+            let adjacent = j == i + 1 || (i == 0 && j == m - 1);
+            !equals(p, r)
+                && (adjacent || !pseudo_intersects(ll.opts.predicates, p, q, r, s))
+                && !on_segment(ll, p, q, r)
+                && !on_segment(ll, r, s, p)
+        })
+    })
+}
+
+// p is on the segment a-b, other than at its ends
+fn on_segment(ll: &LinkedLists, a: &Node, b: &Node, p: &Node) -> bool {
+    !equals(p, a)
+        && !equals(p, b)
+        && ll.opts.predicates.area(a, b, p) == 0.0
+        && p.x >= a.x.min(b.x)
+        && p.x <= a.x.max(b.x)
+        && p.y >= a.y.min(b.y)
+        && p.y <= a.y.max(b.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::linked_list;
+    use super::*;
+
+    // the vertices of each triangle, smallest first
+    fn triangles(m: &[f64], objective: Objective) -> Option<Vec<[usize; 3]>> {
+        let (mut ll, last) = linked_list(m, 0, m.len(), true);
+        let ears = triangulate(&mut ll, last, objective).unwrap()?;
+        let mut t: Vec<[usize; 3]> = ears
+            .iter()
+            .map(|e| {
+                let mut t = e.map(|p| ll.nodes[p].i);
+                t.sort_unstable();
+                t
+            })
+            .collect();
+        t.sort_unstable();
+        Some(t)
+    }
+
+    #[test]
+    fn test_triangulate() {
+        // a flat kite, cut along its short diagonal whatever the objective
+        let kite = [0.0, 0.0, 10.0, -1.0, 20.0, 0.0, 10.0, 1.0];
+        for objective in [Objective::MaxMinAngle, Objective::MinMaxAngle, Objective::MinWeight] {
+            assert!(triangles(&kite, objective) == Some(vec![[0, 1, 3], [1, 2, 3]]));
+        }
+        // here the shorter diagonal, 0-2, makes thinner triangles
+        let quad = [0.0, 0.0, 8.0, 0.0, 9.0, 4.0, 0.0, 6.0];
+        assert!(triangles(&quad, Objective::MinWeight) == Some(vec![[0, 1, 2], [0, 2, 3]]));
+        assert!(triangles(&quad, Objective::MaxMinAngle) == Some(vec![[0, 1, 3], [1, 2, 3]]));
+        assert!(triangles(&quad, Objective::MinMaxAngle) == Some(vec![[0, 1, 3], [1, 2, 3]]));
+        // a vertex on another edge, and a vertex twice
+        let touching = [0.0, 0.0, 4.0, 0.0, 4.0, 4.0, 2.0, 0.0, 0.0, 4.0];
+        assert!(triangles(&touching, Objective::MinWeight).is_none());
+        let twice = [0.0, 0.0, 4.0, 0.0, 2.0, 2.0, 4.0, 4.0, 0.0, 4.0, 2.0, 2.0];
+        assert!(triangles(&twice, Objective::MinWeight).is_none());
+    }
+}
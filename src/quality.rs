@@ -0,0 +1,182 @@
+// the shape of the triangles, see quality_report. deviation says whether
+// the triangles cover the polygon; this says how well shaped they are:
+// their angles, how long and thin they are, how large, and how long
+// their edges are. a triangle of zero area, by the exact orientation
+// test verify uses, is counted as degenerate and left out of the rest
+
+use std::collections::HashSet;
+
+use super::steiner::point;
+use super::verify::{orient, Point};
+use super::{Coord, DIM};
+
+// the shape of one triangle
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TriangleQuality {
+    pub min_angle: f64, // the smallest angle, in degrees
+    pub max_angle: f64, // the largest angle, in degrees
+    // the longest edge over the shortest altitude, scaled so an
+    // equilateral triangle has 1. infinite for zero area
+    pub aspect_ratio: f64,
+    pub area: f64, // unsigned
+}
+
+// how many values fall in each of a number of equal bins, the first
+// starting at 'start'. the last bin holds its upper end
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Histogram {
+    pub start: f64,
+    pub width: f64,
+    pub counts: Vec<usize>,
+}
+
+// quality_report's findings. all but 'degenerate' and 'triangles' are
+// over the triangles of nonzero area only; with none of those, they are 0
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QualityReport {
+    // every triangle, in order, leaving out those with bad indices
+    pub triangles: Vec<TriangleQuality>,
+    pub degenerate: usize,       // triangles of zero area
+    pub min_angle: f64,          // the smallest angle of any triangle
+    pub max_angle: f64,          // the largest angle of any triangle
+    pub mean_min_angle: f64,     // the triangles' smallest angles, averaged
+    pub max_aspect_ratio: f64,   // the longest and thinnest triangle's
+    pub mean_aspect_ratio: f64,  // averaged
+    pub min_area: f64,           // the smallest triangle's area
+    pub max_area: f64,           // the largest triangle's area
+    pub total_area: f64,         // all triangles' area
+    pub min_angles: Histogram,   // the smallest angles, in 10 degree bins to 60
+    pub edge_lengths: Histogram, // every edge once, in 10 bins, shortest to longest
+}
+
+// the shape of the triangle a, b, c
+pub fn triangle_quality(a: Point, b: Point, c: Point) -> TriangleQuality {
+    let (mut min_angle, mut max_angle, mut longest) = (180f64, 0f64, 0f64);
+    for (p, q, r) in [(a, b, c), (b, c, a), (c, a, b)] {
+        let (ux, uy) = (q[0] - p[0], q[1] - p[1]);
+        let (vx, vy) = (r[0] - p[0], r[1] - p[1]);
+        let angle = (ux * vy - uy * vx).abs().atan2(ux * vx + uy * vy).to_degrees();
+        min_angle = min_angle.min(angle);
+        max_angle = max_angle.max(angle);
+        longest = longest.max(ux * ux + uy * uy);
+    }
+    let area = orient(a, b, c).abs() / 2.0;
+    TriangleQuality {
+        min_angle,
+        max_angle,
+        aspect_ratio: match area > 0.0 {
+            true => 3f64.sqrt() * longest / (4.0 * area),
+            false => f64::INFINITY,
+        },
+        area,
+    }
+}
+
+// measure the triangles, indices into data like earcut returns. the
+// first two coordinates of each vertex are used. triangles with an index
+// past the last vertex are left out, as deviation_report does
+pub fn quality_report<T: Coord>(data: &[T], dims: usize, triangles: &[usize]) -> QualityReport {
+    let n = match dims {
+        0 | 1 => 0,
+        _ => data.len() / dims,
+    };
+    let vertex = |i: usize| point(&data[i * dims..i * dims + 2]);
+    let mut report = QualityReport::default();
+    let mut edges = HashSet::new();
+    let (mut min_angles, mut lengths) = (Vec::new(), Vec::new());
+    let mut first = true;
+    for t in triangles.chunks(3).filter(|t| t.len() == 3 && t.iter().all(|&i| i < n)) {
+        let q = triangle_quality(vertex(t[0]), vertex(t[1]), vertex(t[2]));
+        report.total_area += q.area;
+        if q.area == 0.0 {
+            report.degenerate += 1;
+            report.triangles.push(q);
+            continue;
+        }
+        if first {
+            report.min_angle = q.min_angle;
+            report.min_area = q.area;
+            first = false;
+        }
+        report.min_angle = report.min_angle.min(q.min_angle);
+        report.max_angle = report.max_angle.max(q.max_angle);
+        report.max_aspect_ratio = report.max_aspect_ratio.max(q.aspect_ratio);
+        report.min_area = report.min_area.min(q.area);
+        report.max_area = report.max_area.max(q.area);
+        report.mean_min_angle += q.min_angle;
+        report.mean_aspect_ratio += q.aspect_ratio;
+        min_angles.push(q.min_angle);
+        for i in 0..3 {
+            let (a, b) = (t[i], t[(i + 1) % 3]);
+            if edges.insert((a.min(b), a.max(b))) {
+                let (p, q) = (vertex(a), vertex(b));
+                lengths.push((q[0] - p[0]).hypot(q[1] - p[1]));
+            }
+        }
+        report.triangles.push(q);
+    }
+    if !min_angles.is_empty() {
+        report.mean_min_angle /= min_angles.len() as f64;
+        report.mean_aspect_ratio /= min_angles.len() as f64;
+    }
+    report.min_angles = histogram(&min_angles, 0.0, 60.0, 6);
+    let shortest = lengths.iter().cloned().fold(f64::INFINITY, f64::min);
+    let longest = lengths.iter().cloned().fold(0.0, f64::max);
+    report.edge_lengths = match lengths.is_empty() {
+        true => Histogram::default(),
+        false => histogram(&lengths, shortest, longest, 10),
+    };
+    report
+}
+
+// take the triangles of zero area out of 'triangles', made from 'data',
+// see EarcutOptions::drop_degenerate. returns how many there were
+pub(crate) fn drop_degenerate<T: Coord>(data: &[T], triangles: &mut Vec<usize>) -> usize {
+    let vertex = |i: usize| point(&data[i * DIM..]);
+    let before = triangles.len() / 3;
+    *triangles = triangles
+        .chunks(3)
+        .filter(|t| orient(vertex(t[0]), vertex(t[1]), vertex(t[2])) != 0.0)
+        .flatten()
+        .cloned()
+        .collect();
+    before - triangles.len() / 3
+}
+
+// count 'values' in 'bins' equal bins from start to end. values past
+// either end are counted in the bin at that end
+fn histogram(values: &[f64], start: f64, end: f64, bins: usize) -> Histogram {
+    let width = (end - start) / bins as f64;
+    let mut counts = vec![0; bins];
+    for &v in values {
+        let bin = match width > 0.0 {
+            true => ((v - start) / width).floor().max(0.0) as usize,
+            false => 0,
+        };
+        counts[bin.min(bins - 1)] += 1;
+    }
+    Histogram { start, width, counts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_triangle_quality() {
+        let q = triangle_quality([0.0, 0.0], [2.0, 0.0], [1.0, 3f64.sqrt()]);
+        assert!((q.min_angle - 60.0).abs() < 1e-9 && (q.max_angle - 60.0).abs() < 1e-9);
+        assert!((q.aspect_ratio - 1.0).abs() < 1e-12 && (q.area - 3f64.sqrt()).abs() < 1e-12);
+        let q = triangle_quality([0.0, 0.0], [4.0, 0.0], [0.0, 3.0]);
+        assert!((q.max_angle - 90.0).abs() < 1e-9 && q.area == 6.0);
+        let q = triangle_quality([0.0, 0.0], [1.0, 1.0], [2.0, 2.0]);
+        assert!(q.area == 0.0 && q.aspect_ratio == f64::INFINITY && q.max_angle == 180.0);
+    }
+
+    #[test]
+    fn test_histogram() {
+        let h = histogram(&[0.0, 1.0, 2.5, 4.0, 9.0], 0.0, 4.0, 4);
+        assert!(h.start == 0.0 && h.width == 1.0 && h.counts == vec![1, 1, 1, 2]);
+        assert!(histogram(&[3.0, 3.0], 3.0, 3.0, 10).counts[0] == 2);
+    }
+}
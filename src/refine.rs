@@ -0,0 +1,574 @@
+// quality meshes, see earcut_with_refinement. earcut's triangles, after
+// the delaunay pass, are refined the way ruppert's algorithm does it:
+// a triangle that is too big, or has too small an angle, gets a new
+// vertex at its circumcenter, unless that is beyond a ring edge or too
+// close to one, in which case the edge is split in two instead. ring
+// edges, and the pieces they are split into, are the segments; they are
+// never flipped or crossed, and one with a vertex inside the circle it
+// is the diameter of (encroached) is split first
+//
+// new vertices are put in with bowyer and watson's method: the triangles
+// whose circumcircles hold the vertex, not reaching across a segment,
+// are taken out, and the hole they leave is filled with a fan around it.
+// that keeps the triangles constrained delaunay
+//
+// ruppert's algorithm ends for a minimum angle of up to about 20.7
+// degrees, if the rings have no sharper corners than 60 degrees. near a
+// sharp corner, splits would go on forever; segments with an end at an
+// input vertex are split at a power of two from it, so that the pieces
+// near a corner end on circles around it, and a small angle between two
+// such pieces, at the same distance from the corner, is accepted, like
+// Shewchuk's Triangle does. past that, max_points bounds the work
+
+use std::collections::{HashMap, HashSet};
+
+use super::delaunay::{flip, incircle};
+use super::verify::{orient, Point};
+use super::{earcut_with_options, EarcutError, EarcutOptions, DIM};
+
+// what earcut_with_refinement should make of the triangles
+#[derive(Clone, Debug)]
+pub struct Refinement {
+    min_angle: f64,
+    max_area: f64,
+    max_points: usize,
+}
+
+impl Default for Refinement {
+    fn default() -> Refinement {
+        Refinement {
+            min_angle: 0.0,
+            max_area: f64::INFINITY,
+            max_points: 1 << 20,
+        }
+    }
+}
+
+impl Refinement {
+    pub fn new() -> Refinement {
+        Refinement::default()
+    }
+
+    // the smallest angle, in degrees, a triangle may have. up to 20.7 is
+    // sure to work, and up to about 33 usually does; sharper corners of
+    // the polygon itself, and the triangles right next to them, are left
+    // as they are. default 0, any angle
+    pub fn min_angle(mut self, degrees: f64) -> Refinement {
+        self.min_angle = degrees;
+        self
+    }
+
+    // the largest area a triangle may have. default infinity
+    pub fn max_area(mut self, area: f64) -> Refinement {
+        self.max_area = area;
+        self
+    }
+
+    // give up with EarcutError::OverBudget after adding this many
+    // vertices. default 2^20
+    pub fn max_points(mut self, points: usize) -> Refinement {
+        self.max_points = points;
+        self
+    }
+}
+
+// same as earcut_with_options(), plus refinement: vertices are added
+// until every triangle meets 'refinement'. returns the vertices as well:
+// the n vertices of data, flattened the same way, then the added ones.
+// on OverBudget the triangles cover the polygon, only not all of them
+// meet 'refinement'. on other errors no vertex is added. triangles that
+// aren't a clean triangulation, from bad input, aren't refined, and come
+// back as Unrefined
+pub fn earcut_with_refinement(
+    data: &Vec<f64>,
+    hole_indices: &Vec<usize>,
+    dims: usize,
+    refinement: &Refinement,
+    options: &EarcutOptions,
+) -> (Result<Vec<usize>, EarcutError>, Vec<f64>) {
+    let n = data.len() / DIM;
+    let mut vertices = data[..n * DIM].to_vec();
+    let mut triangles = match earcut_with_options(data, hole_indices, dims, options) {
+        Ok(triangles) => triangles,
+        Err(e) => return (Err(e), vertices),
+    };
+    flip(&vertices, hole_indices, &mut triangles);
+    let mut mesh = match Mesh::new(&vertices, hole_indices, &triangles) {
+        Some(mesh) => mesh,
+        None => return (Err(EarcutError::Unrefined { triangles }), vertices),
+    };
+    let done = mesh.refine(refinement);
+    for p in &mesh.points[n..] {
+        vertices.extend_from_slice(p);
+    }
+    let triangles = mesh.triangles();
+    match done {
+        true => (Ok(triangles), vertices),
+        false => (Err(EarcutError::OverBudget { triangles }), vertices),
+    }
+}
+
+// the triangles, counterclockwise, as they are being refined
+struct Mesh {
+    points: Vec<Point>,
+    tris: Vec<[usize; 3]>,
+    alive: Vec<bool>,
+    free: Vec<usize>,
+    // the triangle of each edge, by its direction
+    sides: HashMap<(usize, usize), usize>,
+    // the segments, each with the ring edge it is part of
+    segments: HashMap<(usize, usize), (usize, usize)>,
+    // the ring edge each vertex added on a segment is on
+    on: HashMap<usize, (usize, usize)>,
+    // the number of input vertices
+    inputs: usize,
+}
+
+// where a point is, for walk
+enum Place {
+    In(usize),
+    Beyond((usize, usize)),
+    Lost,
+}
+
+// why insert didn't put a vertex in
+#[derive(Debug)]
+enum Refused {
+    // the vertex would be beyond this segment, or too close to it
+    Segment((usize, usize)),
+    // the vertex can't be put in at all, because of rounding
+    Stuck,
+}
+
+impl Mesh {
+    // None if 'triangles' aren't all counterclockwise, or have an edge
+    // twice the same way
+    fn new(vertices: &[f64], hole_indices: &[usize], triangles: &[usize]) -> Option<Mesh> {
+        let points: Vec<Point> = vertices.chunks_exact(DIM).map(|c| [c[0], c[1]]).collect();
+        let mut mesh = Mesh {
+            inputs: points.len(),
+            points,
+            tris: Vec::new(),
+            alive: Vec::new(),
+            free: Vec::new(),
+            sides: HashMap::new(),
+            segments: HashMap::new(),
+            on: HashMap::new(),
+        };
+        for t in triangles.chunks_exact(3) {
+            let [a, b, c] = [t[0], t[1], t[2]];
+            let o = orient(mesh.points[a], mesh.points[b], mesh.points[c]);
+            if o.is_nan() || o <= 0.0 {
+                return None;
+            }
+            if [(a, b), (b, c), (c, a)].iter().any(|e| mesh.sides.contains_key(e)) {
+                return None;
+            }
+            mesh.add([a, b, c]);
+        }
+        // ring edges, and edges with a triangle on one side only, which
+        // are ring edges that lost a collinear vertex
+        let mut rings = HashSet::new();
+        let mut bounds = vec![0];
+        bounds.extend(hole_indices.iter().cloned());
+        bounds.push(mesh.inputs);
+        for b in bounds.windows(2).filter(|b| b[0] < b[1]) {
+            for i in b[0]..b[1] {
+                rings.insert(edge(i, if i + 1 < b[1] { i + 1 } else { b[0] }));
+            }
+        }
+        let edges: Vec<(usize, usize)> = mesh.sides.keys().cloned().collect();
+        for (a, b) in edges {
+            if rings.contains(&edge(a, b)) || !mesh.sides.contains_key(&(b, a)) {
+                mesh.segments.insert(edge(a, b), edge(a, b));
+            }
+        }
+        Some(mesh)
+    }
+
+    // add vertices until every triangle meets 'r', or max_points are
+    // added. false in that last case
+    fn refine(&mut self, r: &Refinement) -> bool {
+        // ratio of circumradius to shortest edge above which a triangle
+        // has an angle below min_angle
+        let ratio = match r.min_angle > 0.0 {
+            true => 0.5 / r.min_angle.min(60.0).to_radians().sin(),
+            false => f64::INFINITY,
+        };
+        // segments to split: encroached ones, and ones a circumcenter
+        // needs split, which always are. sorted, rather than in the hash
+        // map's order, so the same input always gives the same mesh
+        let mut split: Vec<((usize, usize), bool)> = self.segments.keys().map(|&s| (s, false)).collect();
+        split.sort_unstable();
+        let mut bad: Vec<(usize, [usize; 3])> = (0..self.tris.len()).map(|t| (t, self.tris[t])).collect();
+        let mut stuck: HashSet<(usize, usize)> = HashSet::new();
+        loop {
+            if let Some((s, always)) = split.pop() {
+                if !self.segments.contains_key(&s) || stuck.contains(&s) || !(always || self.encroached(s)) {
+                    continue;
+                }
+                if self.points.len() - self.inputs >= r.max_points {
+                    return false;
+                }
+                let p = self.split_point(s);
+                match self.insert(p, self.beside(s), Some(s)) {
+                    Ok(new) => self.check(&new, r, ratio, &mut split, &mut bad),
+                    Err(_) => {
+                        stuck.insert(s);
+                    }
+                }
+                continue;
+            }
+            let (t, v) = match bad.pop() {
+                Some(next) => next,
+                None => return true,
+            };
+            if !self.alive[t] || self.tris[t] != v || !self.is_bad(v, r, ratio) {
+                continue;
+            }
+            let c = match circumcenter(self.points[v[0]], self.points[v[1]], self.points[v[2]]) {
+                Some(c) => c,
+                None => continue,
+            };
+            if self.points.len() - self.inputs >= r.max_points {
+                return false;
+            }
+            let refused = match self.walk(t, c) {
+                Place::In(u) => match self.insert(c, vec![u], None) {
+                    Ok(new) => {
+                        self.check(&new, r, ratio, &mut split, &mut bad);
+                        continue;
+                    }
+                    Err(refused) => refused,
+                },
+                Place::Beyond(s) => Refused::Segment(s),
+                Place::Lost => Refused::Stuck,
+            };
+            // split the segment in the way, and try the triangle again
+            if let Refused::Segment(s) = refused {
+                if !stuck.contains(&s) {
+                    split.push((s, true));
+                    bad.push((t, v));
+                }
+            }
+        }
+    }
+
+    // queue the segments of the triangles 'new', and the triangles, to
+    // be looked at
+    fn check(
+        &self,
+        new: &[usize],
+        r: &Refinement,
+        ratio: f64,
+        split: &mut Vec<((usize, usize), bool)>,
+        bad: &mut Vec<(usize, [usize; 3])>,
+    ) {
+        for &t in new {
+            let v = self.tris[t];
+            for i in 0..3 {
+                let s = edge(v[i], v[(i + 1) % 3]);
+                if self.segments.contains_key(&s) {
+                    split.push((s, false));
+                }
+            }
+            if self.is_bad(v, r, ratio) {
+                bad.push((t, v));
+            }
+        }
+    }
+
+    // whether triangle v is too big, or too sharp, and can be helped
+    fn is_bad(&self, v: [usize; 3], r: &Refinement, ratio: f64) -> bool {
+        let [a, b, c] = [self.points[v[0]], self.points[v[1]], self.points[v[2]]];
+        let area = orient(a, b, c) / 2.0;
+        if area > r.max_area {
+            return true;
+        }
+        // the circumradius is the product of the edges over 4 times the
+        // area
+        let lengths = [dist(b, c), dist(c, a), dist(a, b)];
+        let shortest = (0..3).min_by(|&i, &j| lengths[i].total_cmp(&lengths[j])).unwrap_or(0);
+        let radius = lengths[0] * lengths[1] * lengths[2] / (4.0 * area);
+        if radius <= ratio * lengths[shortest] {
+            return false;
+        }
+        // the shortest edge joins two pieces of segments that meet at an
+        // input vertex, at the same distance from it: the angle there is
+        // the polygon's own
+        let (u, w) = (v[(shortest + 1) % 3], v[(shortest + 2) % 3]);
+        match (self.on.get(&u), self.on.get(&w)) {
+            (Some(&s), Some(&t)) if s != t => {
+                let apex = [s.0, s.1].iter().cloned().find(|&x| x == t.0 || x == t.1);
+                match apex {
+                    Some(x) => {
+                        let (du, dw) = (dist(self.points[u], self.points[x]), dist(self.points[w], self.points[x]));
+                        (du - dw).abs() > 1e-9 * du.max(dw)
+                    }
+                    None => true,
+                }
+            }
+            _ => true,
+        }
+    }
+
+    // whether a vertex is inside the circle that segment s is the
+    // diameter of. only the far corners of the triangles on s need a look
+    fn encroached(&self, s: (usize, usize)) -> bool {
+        let (a, b) = s;
+        [(a, b), (b, a)].iter().filter_map(|e| self.sides.get(e)).any(|&t| {
+            let p = self.points[third(self.tris[t], a, b)];
+            inside_diameter(self.points[a], self.points[b], p)
+        })
+    }
+
+    // where to split segment s: the middle, or if it has one input vertex
+    // as an end, the power of two distance from that end nearest to it
+    fn split_point(&self, s: (usize, usize)) -> Point {
+        let (a, b) = (self.points[s.0], self.points[s.1]);
+        let half = [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0];
+        let (from, to) = match (s.0 < self.inputs, s.1 < self.inputs) {
+            (true, false) => (a, b),
+            (false, true) => (b, a),
+            _ => return half,
+        };
+        let length = dist(from, to);
+        let d = 2f64.powi((length / 2.0).log2().round() as i32);
+        match d > 0.0 && d < length {
+            true => {
+                let f = d / length;
+                [from[0] + f * (to[0] - from[0]), from[1] + f * (to[1] - from[1])]
+            }
+            false => half,
+        }
+    }
+
+    // find the triangle p is in, from triangle t, crossing the edges p is
+    // beyond. stops at a segment in the way
+    fn walk(&self, mut t: usize, p: Point) -> Place {
+        for step in 0..self.tris.len() + 3 {
+            let v = self.tris[t];
+            let next = (0..3).map(|i| (i + step) % 3).find(|&i| {
+                let (a, b) = (v[i], v[(i + 1) % 3]);
+                orient(self.points[a], self.points[b], p) < 0.0
+            });
+            let (a, b) = match next {
+                None => return Place::In(t),
+                Some(i) => (v[i], v[(i + 1) % 3]),
+            };
+            if self.segments.contains_key(&edge(a, b)) {
+                return Place::Beyond(edge(a, b));
+            }
+            t = match self.sides.get(&(b, a)) {
+                Some(&u) => u,
+                None => return Place::Beyond(edge(a, b)),
+            };
+        }
+        Place::Lost
+    }
+
+    // the triangles on either side of segment s
+    fn beside(&self, s: (usize, usize)) -> Vec<usize> {
+        let (a, b) = s;
+        [(a, b), (b, a)].iter().filter_map(|e| self.sides.get(e)).cloned().collect()
+    }
+
+    // put in a vertex at p, which is in the triangles 'cavity', and on
+    // segment 'on' if given. returns the new triangles
+    fn insert(&mut self, p: Point, mut cavity: Vec<usize>, on: Option<(usize, usize)>) -> Result<Vec<usize>, Refused> {
+        if cavity.is_empty() {
+            return Err(Refused::Stuck);
+        }
+        let mut i = 0;
+        while i < cavity.len() {
+            let v = self.tris[cavity[i]];
+            for j in 0..3 {
+                let (a, b) = (v[j], v[(j + 1) % 3]);
+                if self.segments.contains_key(&edge(a, b)) {
+                    continue;
+                }
+                if let Some(&u) = self.sides.get(&(b, a)) {
+                    let w = self.tris[u];
+                    let holds = incircle(self.points[w[0]], self.points[w[1]], self.points[w[2]], p) > 0.0;
+                    if holds && !cavity.contains(&u) {
+                        cavity.push(u);
+                    }
+                }
+            }
+            i += 1;
+        }
+
+        // the edges around the cavity must all face p. rounding in
+        // incircle can leave some that don't; the triangles beyond those
+        // are taken in too
+        let fan = loop {
+            let mut fan = Vec::new();
+            let mut grow = None;
+            for &t in &cavity {
+                let v = self.tris[t];
+                for j in 0..3 {
+                    let (a, b) = (v[j], v[(j + 1) % 3]);
+                    let beyond = self.sides.get(&(b, a)).cloned();
+                    if beyond.is_some_and(|u| cavity.contains(&u)) || on == Some(edge(a, b)) {
+                        continue;
+                    }
+                    if orient(self.points[a], self.points[b], p) > 0.0 {
+                        fan.push((a, b));
+                        continue;
+                    }
+                    match beyond {
+                        Some(u) if !self.segments.contains_key(&edge(a, b)) => grow = Some(u),
+                        _ => match on {
+                            None => return Err(Refused::Segment(edge(a, b))),
+                            Some(_) => return Err(Refused::Stuck),
+                        },
+                    }
+                }
+            }
+            match grow {
+                Some(u) => cavity.push(u),
+                None => break fan,
+            }
+        };
+        // none of the vertices of the cavity may be inside it, lost, and
+        // its edges must go around once. around a segment with a
+        // triangle on one side only, they stop at its ends
+        let rim: HashSet<usize> = fan.iter().flat_map(|&(a, b)| vec![a, b]).collect();
+        let open = match on {
+            Some(s) => self.beside(s).len() == 1,
+            None => false,
+        };
+        let lost = cavity.iter().any(|&t| self.tris[t].iter().any(|x| !rim.contains(x)));
+        if lost || fan.len() + open as usize != rim.len() {
+            return Err(Refused::Stuck);
+        }
+        // a circumcenter too close to a segment around it splits that
+        if on.is_none() {
+            if let Some(&(a, b)) = fan.iter().find(|&&(a, b)| {
+                self.segments.contains_key(&edge(a, b)) && inside_diameter(self.points[a], self.points[b], p)
+            }) {
+                return Err(Refused::Segment(edge(a, b)));
+            }
+        }
+
+        let x = self.points.len();
+        self.points.push(p);
+        if let Some((a, b)) = on {
+            let whole = self.segments.remove(&(a, b)).unwrap_or((a, b));
+            self.segments.insert(edge(a, x), whole);
+            self.segments.insert(edge(x, b), whole);
+            self.on.insert(x, whole);
+        }
+        for &t in &cavity {
+            self.remove(t);
+        }
+        Ok(fan.into_iter().map(|(a, b)| self.add([a, b, x])).collect())
+    }
+
+    fn add(&mut self, v: [usize; 3]) -> usize {
+        let t = match self.free.pop() {
+            Some(t) => {
+                self.tris[t] = v;
+                self.alive[t] = true;
+                t
+            }
+            None => {
+                self.tris.push(v);
+                self.alive.push(true);
+                self.tris.len() - 1
+            }
+        };
+        for i in 0..3 {
+            self.sides.insert((v[i], v[(i + 1) % 3]), t);
+        }
+        t
+    }
+
+    fn remove(&mut self, t: usize) {
+        let v = self.tris[t];
+        for i in 0..3 {
+            self.sides.remove(&(v[i], v[(i + 1) % 3]));
+        }
+        self.alive[t] = false;
+        self.free.push(t);
+    }
+
+    fn triangles(&self) -> Vec<usize> {
+        (0..self.tris.len()).filter(|&t| self.alive[t]).flat_map(|t| self.tris[t].to_vec()).collect()
+    }
+}
+
+// an undirected edge
+fn edge(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+// the corner of triangle v that isn't a or b
+fn third(v: [usize; 3], a: usize, b: usize) -> usize {
+    v.iter().cloned().find(|&i| i != a && i != b).unwrap_or(v[0])
+}
+
+fn dist(a: Point, b: Point) -> f64 {
+    (a[0] - b[0]).hypot(a[1] - b[1])
+}
+
+// whether p is strictly inside the circle with diameter a b: the angle
+// at p is obtuse
+fn inside_diameter(a: Point, b: Point, p: Point) -> bool {
+    (a[0] - p[0]) * (b[0] - p[0]) + (a[1] - p[1]) * (b[1] - p[1]) < 0.0
+}
+
+// the center of the circle through a, b and c, if they aren't on a line
+fn circumcenter(a: Point, b: Point, c: Point) -> Option<Point> {
+    let (bx, by) = (b[0] - a[0], b[1] - a[1]);
+    let (cx, cy) = (c[0] - a[0], c[1] - a[1]);
+    let d = 2.0 * (bx * cy - by * cx);
+    let (bl, cl) = (bx * bx + by * by, cx * cx + cy * cy);
+    let center = [a[0] + (cy * bl - by * cl) / d, a[1] + (bx * cl - cx * bl) / d];
+    match center[0].is_finite() && center[1].is_finite() {
+        true => Some(center),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circumcenter() {
+        assert!(circumcenter([0.0, 0.0], [2.0, 0.0], [0.0, 2.0]) == Some([1.0, 1.0]));
+        assert!(circumcenter([0.0, 0.0], [1.0, 1.0], [2.0, 2.0]).is_none());
+        assert!(inside_diameter([0.0, 0.0], [2.0, 0.0], [1.0, 0.5]));
+        assert!(!inside_diameter([0.0, 0.0], [2.0, 0.0], [1.0, 1.0]));
+    }
+
+    #[test]
+    fn test_insert() {
+        // a square, a vertex in the middle, and one on the bottom edge
+        let data = vec![0.0, 0.0, 4.0, 0.0, 4.0, 4.0, 0.0, 4.0];
+        let mut mesh = Mesh::new(&data, &[], &[1, 2, 3, 3, 0, 1]).unwrap();
+        assert!(mesh.segments.len() == 4);
+        assert!(mesh.insert([2.0, 2.0], vec![0], None).unwrap().len() == 4);
+        assert!(mesh.insert([2.0, 0.0], mesh.beside((0, 1)), Some((0, 1))).unwrap().len() == 2);
+        assert!(mesh.triangles().len() == 3 * 5);
+        assert!(mesh.segments.get(&(0, 5)) == Some(&(0, 1)) && mesh.on.get(&5) == Some(&(0, 1)));
+        // outside, and at a vertex
+        assert!(matches!(mesh.walk(0, [5.0, 2.0]), Place::Beyond((1, 2))));
+        let t = match mesh.walk(0, [2.0, 2.0]) {
+            Place::In(t) => t,
+            _ => panic!(),
+        };
+        assert!(matches!(mesh.insert([2.0, 2.0], vec![t], None), Err(Refused::Stuck)));
+        // too close to the bottom edge, which is split instead, at a
+        // power of two from the input vertex 0
+        let t = match mesh.walk(0, [1.0, 0.5]) {
+            Place::In(t) => t,
+            _ => panic!(),
+        };
+        assert!(matches!(mesh.insert([1.0, 0.5], vec![t], None), Err(Refused::Segment((0, 5)))));
+        assert!(!mesh.encroached((0, 5)) && mesh.split_point((0, 5)) == [1.0, 0.0]);
+        mesh.insert([1.0, 0.0], mesh.beside((0, 5)), Some((0, 5))).unwrap();
+        assert!(mesh.split_point((0, 6)) == [0.5, 0.0] && mesh.split_point((5, 6)) == [1.5, 0.0]);
+    }
+}
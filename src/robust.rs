@@ -0,0 +1,283 @@
+// adaptive precision orientation test, after Jonathan Shewchuk's
+// "Adaptive Precision Floating-Point Arithmetic and Fast Robust
+// Geometric Predicates", https://www.cs.cmu.edu/~quake/robust.html
+//
+// orient2d returns a value whose sign is always exactly the sign of the
+// determinant, even if the magnitude is only approximate. most calls
+// finish after the first, ordinary floating point, estimate; only nearly
+// collinear points take the slower exact path.
+//
+// products are split into a rounded value and an exact error term with
+// f64::mul_add instead of Dekker's splitting, the result is the same.
+
+const EPSILON: f64 = 1.1102230246251565e-16; // 2^-53
+const RESULTERRBOUND: f64 = (3.0 + 8.0 * EPSILON) * EPSILON;
+const CCWERRBOUND_A: f64 = (3.0 + 16.0 * EPSILON) * EPSILON;
+const CCWERRBOUND_B: f64 = (2.0 + 12.0 * EPSILON) * EPSILON;
+const CCWERRBOUND_C: f64 = (9.0 + 64.0 * EPSILON) * EPSILON * EPSILON;
+
+// positive if a,b,c wind counterclockwise (in a y-up system), negative
+// if clockwise, zero if collinear
+pub fn orient2d(ax: f64, ay: f64, bx: f64, by: f64, cx: f64, cy: f64) -> f64 {
+    let detleft = (ax - cx) * (by - cy);
+    let detright = (ay - cy) * (bx - cx);
+    let det = detleft - detright;
+
+    let detsum = if detleft > 0.0 {
+        if detright <= 0.0 {
+            return det;
+        }
+        detleft + detright
+    } else if detleft < 0.0 {
+        if detright >= 0.0 {
+            return det;
+        }
+        -detleft - detright
+    } else {
+        return det;
+    };
+
+    let errbound = CCWERRBOUND_A * detsum;
+    if det >= errbound || -det >= errbound {
+        return det;
+    }
+    orient2d_adapt(ax, ay, bx, by, cx, cy, detsum)
+}
+
+fn orient2d_adapt(ax: f64, ay: f64, bx: f64, by: f64, cx: f64, cy: f64, detsum: f64) -> f64 {
+    let acx = ax - cx;
+    let bcx = bx - cx;
+    let acy = ay - cy;
+    let bcy = by - cy;
+
+    let (detleft, detlefttail) = two_product(acx, bcy);
+    let (detright, detrighttail) = two_product(acy, bcx);
+    let b = two_two_diff(detleft, detlefttail, detright, detrighttail);
+
+    let mut det = estimate(&b);
+    let errbound = CCWERRBOUND_B * detsum;
+    if det >= errbound || -det >= errbound {
+        return det;
+    }
+
+    let acxtail = two_diff_tail(ax, cx, acx);
+    let bcxtail = two_diff_tail(bx, cx, bcx);
+    let acytail = two_diff_tail(ay, cy, acy);
+    let bcytail = two_diff_tail(by, cy, bcy);
+
+    if acxtail == 0.0 && acytail == 0.0 && bcxtail == 0.0 && bcytail == 0.0 {
+        return det;
+    }
+
+    let errbound = CCWERRBOUND_C * detsum + RESULTERRBOUND * det.abs();
+    det += (acx * bcytail + bcy * acxtail) - (acy * bcxtail + bcx * acytail);
+    if det >= errbound || -det >= errbound {
+        return det;
+    }
+
+    let (s1, s0) = two_product(acxtail, bcy);
+    let (t1, t0) = two_product(acytail, bcx);
+    let u = two_two_diff(s1, s0, t1, t0);
+    let mut c1 = [0.0; 8];
+    let c1len = fast_expansion_sum_zeroelim(&b, &u, &mut c1);
+
+    let (s1, s0) = two_product(acx, bcytail);
+    let (t1, t0) = two_product(acy, bcxtail);
+    let u = two_two_diff(s1, s0, t1, t0);
+    let mut c2 = [0.0; 12];
+    let c2len = fast_expansion_sum_zeroelim(&c1[..c1len], &u, &mut c2);
+
+    let (s1, s0) = two_product(acxtail, bcytail);
+    let (t1, t0) = two_product(acytail, bcxtail);
+    let u = two_two_diff(s1, s0, t1, t0);
+    let mut d = [0.0; 16];
+    let dlen = fast_expansion_sum_zeroelim(&c2[..c2len], &u, &mut d);
+
+    d[dlen - 1]
+}
+
+// orient2d for integer coordinates, exact as long as the differences of
+// the coordinates fit in 63 bits
+#[inline(always)]
+pub fn orient2d_int(ax: i64, ay: i64, bx: i64, by: i64, cx: i64, cy: i64) -> i128 {
+    (ax - cx) as i128 * (by - cy) as i128 - (ay - cy) as i128 * (bx - cx) as i128
+}
+
+// a*b as a rounded product plus its exact rounding error
+#[inline(always)]
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+#[inline(always)]
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let bvirt = x - a;
+    let avirt = x - bvirt;
+    (x, (a - avirt) + (b - bvirt))
+}
+
+#[inline(always)]
+fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    (x, b - (x - a))
+}
+
+// the rounding error of x = a - b
+#[inline(always)]
+fn two_diff_tail(a: f64, b: f64, x: f64) -> f64 {
+    let bvirt = a - x;
+    let avirt = x + bvirt;
+    (a - avirt) + (bvirt - b)
+}
+
+#[inline(always)]
+fn two_diff(a: f64, b: f64) -> (f64, f64) {
+    let x = a - b;
+    (x, two_diff_tail(a, b, x))
+}
+
+// (a1 + a0) - b, as a three component expansion, largest first
+#[inline(always)]
+fn two_one_diff(a1: f64, a0: f64, b: f64) -> (f64, f64, f64) {
+    let (i, x0) = two_diff(a0, b);
+    let (x2, x1) = two_sum(a1, i);
+    (x2, x1, x0)
+}
+
+// (a1 + a0) - (b1 + b0) as a four component expansion, smallest first
+#[inline(always)]
+fn two_two_diff(a1: f64, a0: f64, b1: f64, b0: f64) -> [f64; 4] {
+    let (j, r0, x0) = two_one_diff(a1, a0, b0);
+    let (x3, x2, x1) = two_one_diff(j, r0, b1);
+    [x0, x1, x2, x3]
+}
+
+fn estimate(e: &[f64]) -> f64 {
+    e.iter().sum()
+}
+
+// sum two expansions, dropping zero components. returns the number of
+// components written to h
+fn fast_expansion_sum_zeroelim(e: &[f64], f: &[f64], h: &mut [f64]) -> usize {
+    let (mut ei, mut fi, mut hi) = (0, 0, 0);
+    let mut q;
+    // take the component of smaller magnitude first
+    let e_first = |ei: usize, fi: usize| {
+        fi >= f.len() || (ei < e.len() && ((f[fi] > e[ei]) == (f[fi] > -e[ei])))
+    };
+
+    if e_first(ei, fi) {
+        q = e[ei];
+        ei += 1;
+    } else {
+        q = f[fi];
+        fi += 1;
+    }
+
+    if ei < e.len() && fi < f.len() {
+        let (qnew, hh) = if e_first(ei, fi) {
+            ei += 1;
+            fast_two_sum(e[ei - 1], q)
+        } else {
+            fi += 1;
+            fast_two_sum(f[fi - 1], q)
+        };
+        q = qnew;
+        if hh != 0.0 {
+            h[hi] = hh;
+            hi += 1;
+        }
+    }
+    while ei < e.len() || fi < f.len() {
+        let (qnew, hh) = if e_first(ei, fi) {
+            ei += 1;
+            two_sum(q, e[ei - 1])
+        } else {
+            fi += 1;
+            two_sum(q, f[fi - 1])
+        };
+        q = qnew;
+        if hh != 0.0 {
+            h[hi] = hh;
+            hi += 1;
+        }
+    }
+    if q != 0.0 || hi == 0 {
+        h[hi] = q;
+        hi += 1;
+    }
+    hi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // exact sign for integer valued coordinates
+    fn exact_sign(a: (i64, i64), b: (i64, i64), c: (i64, i64)) -> i32 {
+        let d = (a.0 - c.0) as i128 * (b.1 - c.1) as i128
+            - (a.1 - c.1) as i128 * (b.0 - c.0) as i128;
+        d.signum() as i32
+    }
+
+    fn sign(x: f64) -> i32 {
+        match x {
+            x if x > 0.0 => 1,
+            x if x < 0.0 => -1,
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn test_orient2d_simple() {
+        assert!(orient2d(0.0, 0.0, 1.0, 0.0, 0.0, 1.0) > 0.0);
+        assert!(orient2d(0.0, 0.0, 0.0, 1.0, 1.0, 0.0) < 0.0);
+        assert!(orient2d(0.0, 0.0, 1.0, 1.0, 2.0, 2.0) == 0.0);
+    }
+
+    #[test]
+    fn test_orient2d_near_collinear() {
+        // points near the line y=x, a few ulps apart. scaled by 2^53 all
+        // coordinates are integers, which gives us the exact answer.
+        let ulp = EPSILON;
+        let scale = (1i64 << 53) as f64;
+        let (b, c) = ((12.0, 12.0), (24.0, 24.0));
+        let mut wrong_fast = 0;
+        for i in 0..64 {
+            for j in 0..64 {
+                let a = (0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp);
+                let exact = exact_sign(
+                    ((a.0 * scale) as i64, (a.1 * scale) as i64),
+                    ((b.0 * scale) as i64, (b.1 * scale) as i64),
+                    ((c.0 * scale) as i64, (c.1 * scale) as i64),
+                );
+                assert!(sign(orient2d(a.0, a.1, b.0, b.1, c.0, c.1)) == exact);
+                let naive = (a.0 - c.0) * (b.1 - c.1) - (a.1 - c.1) * (b.0 - c.0);
+                if sign(naive) != exact {
+                    wrong_fast += 1;
+                }
+            }
+        }
+        // make sure the test actually exercises the exact path
+        assert!(wrong_fast > 0);
+    }
+
+    #[test]
+    fn test_orient2d_int() {
+        let big = 1i64 << 52;
+        assert!(orient2d_int(-big, -big, big, big - 1, big - 2, big - 3) < 0);
+        assert!(orient2d_int(-big, -big, big, big, big - 1, big - 1) == 0);
+        assert!(orient2d_int(-big, -big, big, big, big - 1, big) > 0);
+    }
+
+    #[test]
+    fn test_expansion_sum() {
+        let (tiny, tinier) = (2f64.powi(-100), 2f64.powi(-120));
+        let mut h = [0.0; 8];
+        let n = fast_expansion_sum_zeroelim(&[tiny, 1.0], &[tinier, -1.0], &mut h);
+        assert!(n == 1);
+        assert!(h[0] == tiny + tinier);
+    }
+}
@@ -0,0 +1,175 @@
+// steiner points: points inside the polygon, other than the vertices of
+// its rings, that the triangles must have as vertices. earcut takes a
+// hole of a single vertex for one, like in tests/fixtures/steiner.json;
+// earcut_with_steiner checks each point against the polygon first, and
+// then adds the good ones as such holes
+
+use super::verify::{on_segment, orient, Point};
+use super::{check_hole_indices, earcut_with_options, Coord, EarcutError, EarcutOptions, DIM};
+
+pub(crate) type Triangles = Result<Vec<usize>, EarcutError>;
+
+// why a steiner point, or a constraint polyline, wasn't used
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rejected {
+    // outside the outer ring, or inside a hole. so are points with NaN or
+    // infinite coordinates
+    Outside,
+    // on an edge or a vertex of one of the rings. the ends of a constraint
+    // polyline of two points or more may be
+    OnBoundary,
+    // at the same place as an earlier point
+    Duplicate,
+    // a segment of the polyline crosses or touches a ring, other than at
+    // an end of the polyline, another segment of it, or an earlier
+    // polyline
+    Crosses,
+}
+
+// same as earcut_with_options(), plus steiner points, flattened like
+// data. a coordinate left over at the end is ignored, as it is in data.
+// the triangles number steiner point k as vertex n + k, after the n
+// vertices of the polygon. points not strictly inside the polygon are in
+// no triangle, and are returned, by their k, with the reason. on an error
+// about the polygon itself no point is checked, and none is rejected
+pub fn earcut_with_steiner<T: Coord>(
+    data: &Vec<T>,
+    hole_indices: &Vec<usize>,
+    dims: usize,
+    steiner: &[T],
+    options: &EarcutOptions,
+) -> (Triangles, Vec<(usize, Rejected)>) {
+    let n = data.len() / DIM;
+    if dims != DIM || check_hole_indices(hole_indices, n).is_err() {
+        return (earcut_with_options(data, hole_indices, dims, options), Vec::new());
+    }
+    let points: Vec<Point> = data[..n * DIM].chunks(DIM).map(point).collect();
+    let rings = rings(&points, hole_indices);
+
+    let mut all = data[..n * DIM].to_vec();
+    let mut holes = hole_indices.clone();
+    // the k of each steiner point that was added, in order
+    let mut added: Vec<usize> = Vec::new();
+    let mut rejected = Vec::new();
+    for (k, c) in steiner.chunks_exact(DIM).enumerate() {
+        let p = point(c);
+        let why = match locate(&rings, p) {
+            Location::Inside if added.iter().any(|&j| point(&steiner[j * DIM..]) == p) => {
+                Some(Rejected::Duplicate)
+            }
+            Location::Inside => None,
+            Location::Outside => Some(Rejected::Outside),
+            Location::OnBoundary => Some(Rejected::OnBoundary),
+        };
+        match why {
+            Some(why) => rejected.push((k, why)),
+            None => {
+                holes.push(all.len() / DIM);
+                all.extend_from_slice(c);
+                added.push(k);
+            }
+        }
+    }
+
+    let result = earcut_with_options(&all, &holes, dims, options);
+    let result = renumber(result, |i| match i < n {
+        true => i,
+        false => n + added[i - n],
+    });
+    (result, rejected)
+}
+
+pub(crate) fn point<T: Coord>(c: &[T]) -> Point {
+    [c[0].to_f64(), c[1].to_f64()]
+}
+
+// the outer ring and the holes, of the polygon of 'points'. the hole
+// indices must be good, see check_hole_indices
+pub(crate) fn rings<'a>(points: &'a [Point], hole_indices: &[usize]) -> Vec<&'a [Point]> {
+    let mut bounds = vec![0];
+    bounds.extend(hole_indices.iter().cloned());
+    bounds.push(points.len());
+    bounds.windows(2).map(|b| &points[b[0]..b[1]]).collect()
+}
+
+// 'result', with every vertex index i in it replaced by f(i)
+pub(crate) fn renumber<F: Fn(usize) -> usize>(result: Triangles, f: F) -> Triangles {
+    let all = |v: Vec<usize>| v.into_iter().map(&f).collect();
+    match result {
+        Ok(triangles) => Ok(all(triangles)),
+        Err(e) => Err(match e {
+            EarcutError::Unclipped { ring } => EarcutError::Unclipped { ring: all(ring) },
+            EarcutError::NoHoleBridge { hole, outer } => EarcutError::NoHoleBridge {
+                hole: all(hole),
+                outer: all(outer),
+            },
+            EarcutError::OutOfRange { vertex } => EarcutError::OutOfRange { vertex: f(vertex) },
+            EarcutError::OverBudget { triangles } => EarcutError::OverBudget { triangles: all(triangles) },
+            EarcutError::Cancelled { triangles } => EarcutError::Cancelled { triangles: all(triangles) },
+            EarcutError::BadHoleIndex { hole } => EarcutError::BadHoleIndex { hole },
+            EarcutError::Unrefined { triangles } => EarcutError::Unrefined { triangles: all(triangles) },
+        }),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Location {
+    Inside,
+    Outside,
+    OnBoundary,
+}
+
+// where p is, relative to the polygon of 'rings'. a ray from p to the
+// right crosses the rings an odd number of times if p is inside the outer
+// ring and no hole. the tests are exact, like verify's
+pub(crate) fn locate(rings: &[&[Point]], p: Point) -> Location {
+    if !(p[0].is_finite() && p[1].is_finite()) {
+        return Location::Outside;
+    }
+    let mut inside = false;
+    for ring in rings {
+        for i in 0..ring.len() {
+            let (a, b) = (ring[i], ring[(i + 1) % ring.len()]);
+            if on_segment(a, b, p) {
+                return Location::OnBoundary;
+            }
+            // a crosses the ray going up, or b going down. either way p
+            // is left of the edge going up
+            if (a[1] <= p[1]) != (b[1] <= p[1]) {
+                let left = match a[1] <= p[1] {
+                    true => orient(a, b, p) > 0.0,
+                    false => orient(b, a, p) > 0.0,
+                };
+                inside ^= left;
+            }
+        }
+    }
+    match inside {
+        true => Location::Inside,
+        false => Location::Outside,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_locate() {
+        let outer = [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0]];
+        let hole = [[2.0, 2.0], [2.0, 4.0], [4.0, 4.0], [4.0, 2.0]];
+        let rings: Vec<&[Point]> = vec![&outer, &hole];
+        assert!(locate(&rings, [5.0, 5.0]) == Location::Inside);
+        assert!(locate(&rings, [3.0, 3.0]) == Location::Outside);
+        assert!(locate(&rings, [11.0, 5.0]) == Location::Outside);
+        assert!(locate(&rings, [-1.0, 0.0]) == Location::Outside);
+        assert!(locate(&rings, [5.0, 0.0]) == Location::OnBoundary);
+        assert!(locate(&rings, [4.0, 4.0]) == Location::OnBoundary);
+        assert!(locate(&rings, [3.0, 4.0]) == Location::OnBoundary);
+        // level with vertices, whose edges the ray passes through
+        assert!(locate(&rings, [1.0, 2.0]) == Location::Inside);
+        assert!(locate(&rings, [1.0, 4.0]) == Location::Inside);
+        assert!(locate(&rings, [f64::NAN, 5.0]) == Location::Outside);
+        assert!(locate(&[], [0.0, 0.0]) == Location::Outside);
+    }
+}
@@ -0,0 +1,154 @@
+// structured record of what the ear clipping does, step by step, for
+// replaying in the viz/ viewer. only built with the 'trace' feature, see
+// earcut_with_trace. all vertices are given as indices into the input
+// data, like the triangles
+
+use super::{LinkedLists, NodeIdx, Predicates, VertIdx, NULL};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceEvent {
+    // the linked list for a ring was built; vertices in list order
+    Ring { ring: Vec<VertIdx>, hole: bool },
+    // a hole was joined to the outer ring by a bridge between two vertices
+    Bridge { hole: VertIdx, outer: VertIdx },
+    // the nodes of a ring were sorted along the z-order curve
+    ZOrder { order: Vec<VertIdx> },
+    // a-b-c was clipped as an ear
+    Ear { a: VertIdx, b: VertIdx, c: VertIdx },
+    // a-b-c is not an ear; 'blocker' is a vertex inside the triangle,
+    // None if the angle at b is reflex
+    NotEar {
+        a: VertIdx,
+        b: VertIdx,
+        c: VertIdx,
+        blocker: Option<VertIdx>,
+    },
+    // cure_local_intersections cut off triangle a-b-c
+    Cure { a: VertIdx, b: VertIdx, c: VertIdx },
+    // no ear was left, so 'pass' starts on what remains of the ring
+    Pass { pass: usize, ring: Vec<VertIdx> },
+    // split_earcut cut the polygon in two along the diagonal a-b
+    Split { a: VertIdx, b: VertIdx },
+}
+
+impl TraceEvent {
+    // one JSON object, like {"event":"ear","a":1,"b":2,"c":3}
+    pub fn to_json(&self) -> String {
+        match self {
+            TraceEvent::Ring { ring, hole } => {
+                format!(r#"{{"event":"ring","ring":{:?},"hole":{}}}"#, ring, hole)
+            }
+            TraceEvent::Bridge { hole, outer } => {
+                format!(r#"{{"event":"bridge","hole":{},"outer":{}}}"#, hole, outer)
+            }
+            TraceEvent::ZOrder { order } => {
+                format!(r#"{{"event":"zorder","order":{:?}}}"#, order)
+            }
+            TraceEvent::Ear { a, b, c } => {
+                format!(r#"{{"event":"ear","a":{},"b":{},"c":{}}}"#, a, b, c)
+            }
+            TraceEvent::NotEar { a, b, c, blocker } => format!(
+                r#"{{"event":"notear","a":{},"b":{},"c":{},"blocker":{}}}"#,
+                a,
+                b,
+                c,
+                blocker.map_or("null".to_string(), |v| v.to_string())
+            ),
+            TraceEvent::Cure { a, b, c } => {
+                format!(r#"{{"event":"cure","a":{},"b":{},"c":{}}}"#, a, b, c)
+            }
+            TraceEvent::Pass { pass, ring } => {
+                format!(r#"{{"event":"pass","pass":{},"ring":{:?}}}"#, pass, ring)
+            }
+            TraceEvent::Split { a, b } => {
+                format!(r#"{{"event":"split","a":{},"b":{}}}"#, a, b)
+            }
+        }
+    }
+}
+
+// a JSON array of the events, one per line
+pub fn trace_json(events: &[TraceEvent]) -> String {
+    let lines: Vec<String> = events.iter().map(|e| e.to_json()).collect();
+    format!("[\n{}\n]", lines.join(",\n"))
+}
+
+// the vertex that keeps prev-ear-next from being an ear, found the slow
+// way, like is_ear does. None if the ear is reflex or nothing blocks it
+pub(crate) fn ear_blocker(
+    ll: &LinkedLists,
+    prev: NodeIdx,
+    ear: NodeIdx,
+    next: NodeIdx,
+) -> Option<VertIdx> {
+    let (a, b, c) = (&ll.nodes[prev], &ll.nodes[ear], &ll.nodes[next]);
+    let pred: Predicates = ll.opts.predicates;
+    if pred.area(a, b, c) >= 0.0 {
+        return None;
+    }
+    ll.iter(c.next_idx..a.idx)
+        .find(|p| {
+            p.idx != a.idx
+                && p.idx != c.idx
+                && pred.point_in_triangle(a, b, c, p)
+                && pred.area(&ll.nodes[p.prev_idx], p, &ll.nodes[p.next_idx]) >= 0.0
+                && !(ll.constraints_from != usize::MAX
+                    && super::touches_corner(
+                        pred,
+                        a,
+                        b,
+                        c,
+                        &ll.nodes[p.prev_idx],
+                        p,
+                        &ll.nodes[p.next_idx],
+                    ))
+        })
+        .map(|p| p.i)
+}
+
+// vertices of the ring at 'start' in z-order, after index_curve
+pub(crate) fn zorder_indices(ll: &LinkedLists, start: NodeIdx) -> Vec<VertIdx> {
+    let mut p = start;
+    while ll.nodes[p].prevz_idx != NULL {
+        p = ll.nodes[p].prevz_idx;
+    }
+    let mut order = Vec::new();
+    while p != NULL {
+        order.push(ll.nodes[p].i);
+        p = ll.nodes[p].nextz_idx;
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let events = vec![
+            TraceEvent::Ring {
+                ring: vec![0, 1, 2],
+                hole: false,
+            },
+            TraceEvent::NotEar {
+                a: 0,
+                b: 1,
+                c: 2,
+                blocker: None,
+            },
+            TraceEvent::NotEar {
+                a: 0,
+                b: 1,
+                c: 2,
+                blocker: Some(3),
+            },
+        ];
+        assert!(
+            trace_json(&events)
+                == "[\n{\"event\":\"ring\",\"ring\":[0, 1, 2],\"hole\":false},\n\
+                    {\"event\":\"notear\",\"a\":0,\"b\":1,\"c\":2,\"blocker\":null},\n\
+                    {\"event\":\"notear\",\"a\":0,\"b\":1,\"c\":2,\"blocker\":3}\n]"
+        );
+    }
+}
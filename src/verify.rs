@@ -0,0 +1,581 @@
+// checks of a triangulation that deviation() can't make. deviation only
+// compares total areas, so triangles that overlap can make up for ones
+// that are missing, and still give 0. verify looks at the triangles
+// themselves. the tests are exact, with the robust predicates.
+// deviation_report breaks the deviation down by ring
+
+use std::collections::HashMap;
+
+use super::robust::orient2d;
+use super::Coord;
+
+pub(crate) type Point = [f64; 2];
+
+// what verify found. triangles are numbered by their position in the
+// triangles list, the first three indices are triangle 0
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Verification {
+    pub bad_indices: Vec<usize>, // triangles with an index past the last vertex
+    pub degenerate: Vec<usize>,  // triangles of zero area
+    pub inverted: Vec<usize>,    // triangles wound clockwise, unlike earcut's
+    pub overlaps: Vec<Overlap>,  // triangles whose insides overlap
+    // input edges, as pairs of vertex indices, that aren't covered by the
+    // edges of the triangles exactly once
+    pub uncovered: Vec<(usize, usize)>,
+    pub gaps: Vec<Vec<Point>>, // parts of the polygon no triangle covers
+    // parts covered by more triangles than they should be: outside the
+    // polygon, or by overlapping triangles
+    pub excess: Vec<Vec<Point>>,
+    pub triangles: usize,         // the number of triangles
+    // n + 2h - 2, for n vertices and h holes: the number of triangles when
+    // no vertex is dropped, and the most there can be
+    pub expected: usize,
+}
+
+// two triangles and the region they both cover
+#[derive(Clone, Debug, PartialEq)]
+pub struct Overlap {
+    pub a: usize,
+    pub b: usize,
+    pub region: Vec<Point>,
+}
+
+impl Verification {
+    // whether every check passed. fewer triangles than expected are fine,
+    // earcut drops duplicate and collinear vertices
+    pub fn is_valid(&self) -> bool {
+        self.bad_indices.is_empty()
+            && self.degenerate.is_empty()
+            && self.inverted.is_empty()
+            && self.overlaps.is_empty()
+            && self.uncovered.is_empty()
+            && self.gaps.is_empty()
+            && self.excess.is_empty()
+            && self.triangles <= self.expected
+    }
+}
+
+// check 'triangles' against the polygon they were made from. the
+// arguments are the same as for deviation(). with fewer than two
+// dimensions there are no vertices, every triangle has bad indices
+pub fn verify<T: Coord>(
+    data: &[T],
+    hole_indices: &[usize],
+    dims: usize,
+    triangles: &[usize],
+) -> Verification {
+    let n = match dims {
+        0 | 1 => 0,
+        _ => data.len() / dims,
+    };
+    let point = |i: usize| [data[i * dims].to_f64() + 0.0, data[i * dims + 1].to_f64() + 0.0];
+    let mut v = Verification {
+        triangles: triangles.len().div_ceil(3),
+        expected: (n + 2 * hole_indices.len()).saturating_sub(2),
+        ..Verification::default()
+    };
+
+    // the triangles that can be looked at, counterclockwise
+    let mut tris: Vec<(usize, [Point; 3])> = Vec::new();
+    for (t, tri) in triangles.chunks(3).enumerate() {
+        if tri.len() < 3 || tri.iter().any(|&i| i >= n) {
+            v.bad_indices.push(t);
+            continue;
+        }
+        let (a, b, c) = (point(tri[0]), point(tri[1]), point(tri[2]));
+        let o = orient(a, b, c);
+        if o == 0.0 {
+            v.degenerate.push(t);
+        } else if o < 0.0 {
+            v.inverted.push(t);
+            tris.push((t, [a, c, b]));
+        } else {
+            tris.push((t, [a, b, c]));
+        }
+    }
+    v.overlaps = overlaps(&tris);
+
+    // the boundary of the polygon, as edges, outer ring counterclockwise
+    // and holes clockwise. from it, take away the edges of the triangles;
+    // edges between two triangles cancel out. what is left bounds the
+    // gaps, and the triangles outside the polygon
+    let mut rings = vec![0];
+    rings.extend(hole_indices.iter().map(|&h| h.min(n)));
+    rings.push(n);
+    let mut chain = Chain::default();
+    for r in 0..rings.len() - 1 {
+        let (start, end) = (rings[r], rings[r + 1].max(rings[r]));
+        let area: f64 = (start..end)
+            .map(|i| {
+                let (p, q) = (point(i), point(if i + 1 < end { i + 1 } else { start }));
+                (q[0] - p[0]) * (q[1] + p[1])
+            })
+            .sum();
+        // area is positive for clockwise rings
+        let flip = (area > 0.0) == (r == 0);
+        for i in start..end {
+            let j = if i + 1 < end { i + 1 } else { start };
+            let (p, q) = (point(i), point(j));
+            if p != q {
+                match flip {
+                    true => chain.add(q, p, 1, Some((i, j))),
+                    false => chain.add(p, q, 1, Some((i, j))),
+                }
+            }
+        }
+    }
+    for &(_, [a, b, c]) in &tris {
+        chain.add(a, b, -1, None);
+        chain.add(b, c, -1, None);
+        chain.add(c, a, -1, None);
+    }
+    // edges that are cut short by a dropped collinear vertex only cancel
+    // once they are split where the other edges end. splitting them where
+    // they cross, too, makes the rings around gaps simple
+    let chain = chain.split();
+    v.uncovered = chain.uncovered();
+    for ring in chain.loops() {
+        match ring_area(&ring) > 0.0 {
+            true => v.gaps.push(ring),
+            false => v.excess.push(ring),
+        }
+    }
+    v
+}
+
+// the areas deviation() compares, per ring, added up with compensated
+// summation so that rounding doesn't hide, or pass for, real errors
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeviationReport {
+    pub polygon_area: f64,   // the outer ring's area, minus the holes'
+    pub triangles_area: f64, // the area of all triangles
+    pub absolute: f64,       // |triangles_area - polygon_area|
+    pub relative: f64,       // absolute / polygon_area, like deviation()
+    pub outside: f64,        // triangle area outside the outer ring
+    pub rings: Vec<RingDeviation>, // the outer ring, then the holes
+}
+
+// how much triangle area a ring holds, against how much it should. for
+// the outer ring, the area inside the holes doesn't count; for a hole,
+// all of it is an error
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RingDeviation {
+    pub area: f64,     // the area of the ring
+    pub covered: f64,  // triangle area inside the ring
+    pub absolute: f64, // how far the triangle area is off
+    pub relative: f64, // absolute, relative to the ring's area
+}
+
+// deviation(), broken down by ring, and with absolute as well as
+// relative errors. triangles with bad indices are left out, see verify
+pub fn deviation_report<T: Coord>(
+    data: &[T],
+    hole_indices: &[usize],
+    dims: usize,
+    triangles: &[usize],
+) -> DeviationReport {
+    let n = match dims {
+        0 | 1 => 0,
+        _ => data.len() / dims,
+    };
+    let point = |i: usize| [data[i * dims].to_f64(), data[i * dims + 1].to_f64()];
+    let tris: Vec<[Point; 3]> = triangles
+        .chunks(3)
+        .filter(|t| t.len() == 3 && t.iter().all(|&i| i < n))
+        .map(|t| match orient(point(t[0]), point(t[1]), point(t[2])) < 0.0 {
+            true => [point(t[0]), point(t[2]), point(t[1])],
+            false => [point(t[0]), point(t[1]), point(t[2])],
+        })
+        .collect();
+    let triangles_area = compensated_sum(tris.iter().map(|t| ring_area(t) / 2.0));
+
+    let mut bounds = vec![0];
+    bounds.extend(hole_indices.iter().map(|&h| h.min(n)));
+    bounds.push(n);
+    let mut rings = Vec::new();
+    for r in 0..bounds.len() - 1 {
+        let ring: Vec<Point> = (bounds[r]..bounds[r + 1].max(bounds[r])).map(point).collect();
+        let area = ring_area(&ring).abs() / 2.0;
+        let covered = compensated_sum(tris.iter().map(|t| area_inside(t, &ring)));
+        rings.push(RingDeviation {
+            area,
+            covered,
+            absolute: covered,
+            relative: relative(covered, area),
+        });
+    }
+
+    let holes_area = compensated_sum(rings[1..].iter().map(|r| r.area));
+    let holes_covered = compensated_sum(rings[1..].iter().map(|r| r.covered));
+    let polygon_area = rings[0].area - holes_area;
+    let absolute = (triangles_area - polygon_area).abs();
+    let outside = (triangles_area - rings[0].covered).max(0.0);
+    let outer = &mut rings[0];
+    outer.absolute = (outer.covered - holes_covered - polygon_area).abs();
+    outer.relative = relative(outer.absolute, polygon_area);
+    DeviationReport {
+        polygon_area,
+        triangles_area,
+        absolute,
+        relative: relative(absolute, polygon_area),
+        outside,
+        rings,
+    }
+}
+
+// an error relative to an area, where no error in no area is none at all
+fn relative(absolute: f64, area: f64) -> f64 {
+    match absolute == 0.0 {
+        true => 0.0,
+        false => absolute / area,
+    }
+}
+
+// the area of counterclockwise triangle t inside the ring. clipping the
+// ring is only needed where its edges come near the triangle; if none do,
+// the triangle is either all inside, or all outside
+fn area_inside(t: &[Point; 3], ring: &[Point]) -> f64 {
+    let min = |k: usize| t[0][k].min(t[1][k]).min(t[2][k]);
+    let max = |k: usize| t[0][k].max(t[1][k]).max(t[2][k]);
+    let (x, y) = ((t[0][0] + t[1][0] + t[2][0]) / 3.0, (t[0][1] + t[1][1] + t[2][1]) / 3.0);
+    let mut inside = false;
+    for i in 0..ring.len() {
+        let (p, q) = (ring[i], ring[(i + 1) % ring.len()]);
+        if p[0].max(q[0]) >= min(0)
+            && p[0].min(q[0]) <= max(0)
+            && p[1].max(q[1]) >= min(1)
+            && p[1].min(q[1]) <= max(1)
+        {
+            return ring_area(&clip(t, ring)).abs() / 2.0;
+        }
+        if (p[1] > y) != (q[1] > y) && x < p[0] + (q[0] - p[0]) * (y - p[1]) / (q[1] - p[1]) {
+            inside = !inside;
+        }
+    }
+    match inside {
+        true => ring_area(t) / 2.0,
+        false => 0.0,
+    }
+}
+
+// Neumaier's variant of Kahan summation: the rounding error of every
+// addition is kept, and added back in at the end
+fn compensated_sum<I: Iterator<Item = f64>>(values: I) -> f64 {
+    let (mut sum, mut c) = (0.0f64, 0.0f64);
+    for x in values {
+        let t = sum + x;
+        c += match sum.abs() >= x.abs() {
+            true => (sum - t) + x,
+            false => (x - t) + sum,
+        };
+        sum = t;
+    }
+    sum + c
+}
+
+// exact sign of the turn a-b-c, positive if counterclockwise
+pub(crate) fn orient(a: Point, b: Point, c: Point) -> f64 {
+    orient2d(a[0], a[1], b[0], b[1], c[0], c[1])
+}
+
+// twice the area of a ring, positive if counterclockwise. taken around
+// the first point, which keeps the products small
+fn ring_area(ring: &[Point]) -> f64 {
+    let n = ring.len();
+    if n < 3 {
+        return 0.0;
+    }
+    let o = ring[0];
+    compensated_sum((1..n - 1).map(|i| {
+        let (p, q) = (ring[i], ring[i + 1]);
+        (p[0] - o[0]) * (q[1] - o[1]) - (q[0] - o[0]) * (p[1] - o[1])
+    }))
+}
+
+// pairs of triangles whose insides overlap. the triangles are swept by
+// the left side of their bounding boxes, so only triangles whose boxes
+// overlap are compared
+fn overlaps(tris: &[(usize, [Point; 3])]) -> Vec<Overlap> {
+    let bbox = |t: &[Point; 3]| {
+        let xs = t.iter().map(|p| p[0]);
+        let ys = t.iter().map(|p| p[1]);
+        (
+            xs.clone().fold(f64::MAX, f64::min),
+            xs.fold(f64::MIN, f64::max),
+            ys.clone().fold(f64::MAX, f64::min),
+            ys.fold(f64::MIN, f64::max),
+        )
+    };
+    let mut sorted: Vec<_> = tris.iter().map(|(t, pts)| (bbox(pts), *t, *pts)).collect();
+    sorted.sort_by(|a, b| a.0 .0.partial_cmp(&b.0 .0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut found = Vec::new();
+    for (i, &(ba, ta, a)) in sorted.iter().enumerate() {
+        for &(bb, tb, b) in sorted[i + 1..].iter().take_while(|(bb, _, _)| bb.0 < ba.1) {
+            if bb.2 < ba.3 && ba.2 < bb.3 && !separated(&a, &b) && !separated(&b, &a) {
+                found.push(Overlap {
+                    a: ta.min(tb),
+                    b: ta.max(tb),
+                    region: clip(&a, &b),
+                });
+            }
+        }
+    }
+    found.sort_by_key(|o| (o.a, o.b));
+    found
+}
+
+// whether an edge of counterclockwise triangle t has all of u on its
+// outside, or on the edge. two triangles whose insides don't overlap
+// always have such an edge, in one or the other
+fn separated(t: &[Point; 3], u: &[Point; 3]) -> bool {
+    (0..3).any(|k| u.iter().all(|&r| orient(t[k], t[(k + 1) % 3], r) <= 0.0))
+}
+
+// the part of polygon u inside counterclockwise triangle t, by cutting
+// away what is outside each edge of t (Sutherland-Hodgman). if u isn't
+// convex, the result can have edges that double back, but its area is
+// still right
+fn clip(t: &[Point; 3], u: &[Point]) -> Vec<Point> {
+    let mut poly = u.to_vec();
+    for k in 0..3 {
+        let (p, q) = (t[k], t[(k + 1) % 3]);
+        let side = |r: Point| orient(p, q, r);
+        let mut out = Vec::new();
+        for i in 0..poly.len() {
+            let (r, s) = (poly[i], poly[(i + 1) % poly.len()]);
+            let (sr, ss) = (side(r), side(s));
+            if sr >= 0.0 {
+                out.push(r);
+            }
+            if (sr > 0.0 && ss < 0.0) || (sr < 0.0 && ss > 0.0) {
+                let f = sr / (sr - ss);
+                out.push([r[0] + f * (s[0] - r[0]), r[1] + f * (s[1] - r[1])]);
+            }
+        }
+        poly = out;
+        if poly.is_empty() {
+            break;
+        }
+    }
+    poly
+}
+
+// edges with a multiplicity, where an edge p-q is the same as q-p taken
+// negatively. each edge remembers the input edges it is part of
+#[derive(Default)]
+struct Chain {
+    edges: HashMap<(Key, Key), Edge>,
+}
+
+// the lower and higher end, the multiplicity from lower to higher, and
+// the input edges
+type Edge = (Point, Point, i32, Vec<(usize, usize)>);
+
+type Key = (u64, u64);
+
+fn key(p: Point) -> Key {
+    (p[0].to_bits(), p[1].to_bits())
+}
+
+impl Chain {
+    fn add(&mut self, p: Point, q: Point, weight: i32, from: Option<(usize, usize)>) {
+        let (kp, kq) = (key(p), key(q));
+        let (k, lo, hi, w) = match kp < kq {
+            true => ((kp, kq), p, q, weight),
+            false => ((kq, kp), q, p, -weight),
+        };
+        let e = self.edges.entry(k).or_insert((lo, hi, 0, Vec::new()));
+        e.2 += w;
+        e.3.extend(from);
+    }
+
+    // the edges that didn't cancel out, split wherever another one of
+    // them ends on them or crosses them, and cancelled again
+    fn split(self) -> Chain {
+        let left: Vec<_> = self.edges.into_values().filter(|e| e.2 != 0).collect();
+        let mut cuts: Vec<Vec<Point>> = vec![Vec::new(); left.len()];
+        for i in 0..left.len() {
+            for j in i + 1..left.len() {
+                let (p, q) = (left[i].0, left[i].1);
+                let (r, s) = (left[j].0, left[j].1);
+                for &(k, a, b, c) in &[(i, p, q, r), (i, p, q, s), (j, r, s, p), (j, r, s, q)] {
+                    if c != a && c != b && on_segment(a, b, c) {
+                        cuts[k].push(c);
+                    }
+                }
+                if let Some(x) = crossing(p, q, r, s) {
+                    cuts[i].push(x);
+                    cuts[j].push(x);
+                }
+            }
+        }
+        let mut chain = Chain::default();
+        for ((p, q, w, from), mut cuts) in left.into_iter().zip(cuts) {
+            let d = |r: &Point| (r[0] - p[0]).abs() + (r[1] - p[1]).abs();
+            cuts.sort_by(|a, b| d(a).partial_cmp(&d(b)).unwrap_or(std::cmp::Ordering::Equal));
+            cuts.dedup();
+            cuts.insert(0, p);
+            cuts.push(q);
+            for c in cuts.windows(2) {
+                chain.add(c[0], c[1], w, None);
+                let e = chain.edges.get_mut(&order(key(c[0]), key(c[1]))).unwrap();
+                e.3.extend(from.iter().cloned());
+            }
+        }
+        chain.edges.retain(|_, e| e.2 != 0);
+        chain
+    }
+
+    fn uncovered(&self) -> Vec<(usize, usize)> {
+        let mut edges: Vec<_> = self.edges.values().flat_map(|e| e.3.iter().cloned()).collect();
+        edges.sort();
+        edges.dedup();
+        edges
+    }
+
+    // the edges, joined end to end into rings. a walk that comes back to
+    // a point it has been at closes a ring there, so the rings are simple
+    fn loops(&self) -> Vec<Vec<Point>> {
+        let mut out: HashMap<Key, Vec<Point>> = HashMap::new();
+        for &(lo, hi, w, _) in self.edges.values() {
+            let (p, q) = if w > 0 { (lo, hi) } else { (hi, lo) };
+            for _ in 0..w.abs() {
+                out.entry(key(p)).or_default().push(q);
+            }
+        }
+        let mut starts: Vec<Key> = out.keys().cloned().collect();
+        starts.sort();
+        let mut rings = Vec::new();
+        for s in starts {
+            let mut path = vec![[f64::from_bits(s.0), f64::from_bits(s.1)]];
+            let mut at: HashMap<Key, usize> = HashMap::new();
+            at.insert(s, 0);
+            while let Some(q) = out.get_mut(&key(path[path.len() - 1])).and_then(|v| v.pop()) {
+                if let Some(&i) = at.get(&key(q)) {
+                    let ring = path.split_off(i + 1);
+                    for p in &ring {
+                        at.remove(&key(*p));
+                    }
+                    rings.push(std::iter::once(q).chain(ring).collect());
+                } else {
+                    at.insert(key(q), path.len());
+                    path.push(q);
+                }
+            }
+            // edges that don't close up into a ring; give what there is
+            if path.len() > 1 {
+                rings.push(path);
+            }
+        }
+        rings
+    }
+}
+
+fn order(a: Key, b: Key) -> (Key, Key) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+// where segments p-q and r-s cross, if they do, other than at an end
+pub(crate) fn crossing(p: Point, q: Point, r: Point, s: Point) -> Option<Point> {
+    let (o1, o2) = (orient(p, q, r), orient(p, q, s));
+    let (o3, o4) = (orient(r, s, p), orient(r, s, q));
+    if o1 * o2 >= 0.0 || o3 * o4 >= 0.0 {
+        return None;
+    }
+    let f = o3 / (o3 - o4);
+    Some([p[0] + f * (q[0] - p[0]), p[1] + f * (q[1] - p[1])])
+}
+
+// whether r is on the segment p-q
+pub(crate) fn on_segment(p: Point, q: Point, r: Point) -> bool {
+    orient(p, q, r) == 0.0
+        && r[0] >= p[0].min(q[0])
+        && r[0] <= p[0].max(q[0])
+        && r[1] >= p[1].min(q[1])
+        && r[1] <= p[1].max(q[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_square() {
+        let data = vec![0.0, 0.0, 2.0, 0.0, 2.0, 2.0, 0.0, 2.0];
+        let v = verify(&data, &[], 2, &[1, 2, 3, 3, 0, 1]);
+        assert!(v.is_valid());
+        assert!(v.triangles == 2 && v.expected == 2);
+
+        // the same area, but overlapping and leaving a gap
+        let v = verify(&data, &[], 2, &[1, 2, 3, 0, 1, 2]);
+        assert!(!v.is_valid());
+        assert!(v.overlaps.len() == 1);
+        assert!(ring_area(&v.overlaps[0].region) == 2.0);
+        assert!(v.gaps.len() == 1 && ring_area(&v.gaps[0]) == 2.0);
+        assert!(v.excess.len() == 1 && ring_area(&v.excess[0]) == -2.0);
+        assert!(v.uncovered == vec![(1, 2), (3, 0)]);
+    }
+
+    #[test]
+    fn test_verify_indices() {
+        let data = vec![0.0, 0.0, 2.0, 0.0, 2.0, 2.0, 0.0, 2.0];
+        let v = verify(&data, &[], 2, &[1, 2, 3, 3, 0, 1, 0, 1, 4, 0, 1]);
+        assert!(v.bad_indices == vec![2, 3]);
+        let v = verify(&data, &[], 2, &[1, 3, 2, 3, 0, 1, 0, 1, 1]);
+        assert!(v.inverted == vec![0]);
+        assert!(v.degenerate == vec![2]);
+    }
+
+    #[test]
+    fn test_verify_collinear() {
+        // vertex 1 is in the middle of an edge, and dropped
+        let data = vec![0.0, 0.0, 1.0, 0.0, 2.0, 0.0, 2.0, 2.0, 0.0, 2.0];
+        let v = verify(&data, &[], 2, &[2, 3, 4, 4, 0, 2]);
+        assert!(v.is_valid());
+        let v = verify(&data, &[], 2, &[2, 3, 4]);
+        assert!(v.gaps.len() == 1 && ring_area(&v.gaps[0]) == 2.0 * 2.0);
+        assert!(v.uncovered == vec![(0, 1), (1, 2), (4, 0)]);
+    }
+
+    #[test]
+    fn test_verify_outside() {
+        // a hole that isn't left out of the triangles
+        let data = vec![0.0, 0.0, 3.0, 0.0, 3.0, 3.0, 0.0, 3.0, 1.0, 1.0, 2.0, 1.0, 2.0, 2.0];
+        let v = verify(&data, &[4], 2, &[1, 2, 3, 3, 0, 1]);
+        assert!(v.gaps.is_empty());
+        assert!(v.excess.len() == 1 && ring_area(&v.excess[0]) == -1.0);
+    }
+
+    #[test]
+    fn test_deviation_report() {
+        let data = vec![0.0, 0.0, 3.0, 0.0, 3.0, 3.0, 0.0, 3.0, 1.0, 1.0, 2.0, 1.0, 2.0, 2.0];
+        // the hole is covered, and one triangle sticks out of the square
+        let r = deviation_report(&data, &[4], 2, &[1, 2, 3, 3, 0, 1, 2, 3, 2]);
+        assert!(r.polygon_area == 9.0 - 0.5 && r.triangles_area == 9.0);
+        assert!(r.absolute == 0.5 && r.relative == 0.5 / 8.5);
+        assert!(r.outside == 0.0);
+        assert!(r.rings.len() == 2);
+        assert!(r.rings[0].covered == 9.0 && r.rings[0].absolute == 0.0);
+        assert!(r.rings[1].area == 0.5 && r.rings[1].covered == 0.5);
+        assert!(r.rings[1].relative == 1.0);
+
+        let r = deviation_report(&data[..8], &[], 2, &[1, 2, 3, 3, 0, 1, 0, 1, 3]);
+        assert!(r.absolute == 4.5 && r.rings[0].absolute == 4.5);
+        // a triangle in the notch of an L
+        let l = [0.0, 0.0, 2.0, 0.0, 2.0, 1.0, 1.0, 1.0, 1.0, 2.0, 0.0, 2.0];
+        let r = deviation_report(&l, &[], 2, &[2, 4, 3]);
+        assert!(r.outside == 0.5 && r.rings[0].covered == 0.0);
+    }
+
+    #[test]
+    fn test_compensated_sum() {
+        let values = vec![1.0, 1e100, 1.0, -1e100];
+        assert!(values.iter().sum::<f64>() == 0.0);
+        assert!(compensated_sum(values.into_iter()) == 2.0);
+    }
+}
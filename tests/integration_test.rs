@@ -39,8 +39,11 @@ fn parse_json(rawdata: &str) -> Option<Vec<Vec<Vec<f64>>>> {
                             let points = contour[j].as_array().unwrap();
                             let mut vp: Vec<f64> = Vec::new();
                             for k in 0..points.len() {
-                                let val = points[k].to_string();
-                                let pval = val.parse::<f64>().unwrap();
+                                // null stands for NaN, see earcutr::fixture_json
+                                let pval = match points[k].is_null() {
+                                    true => f64::NAN,
+                                    false => points[k].to_string().parse::<f64>().unwrap(),
+                                };
                                 vp.push(pval);
                             }
                             vc.push(vp);
@@ -97,10 +100,36 @@ fn mkoutput(
         r###"testOutput["{}"]["report"]={:?};"###,
         filename, rpt
     ));
+    // the viewer replays the trace, if there is one
+    #[cfg(feature = "trace")]
+    {
+        let (flat, holeidxs, dims) = earcutr::flatten(data);
+        let opts = earcutr::EarcutOptions::new();
+        let (_, trace) = earcutr::earcut_with_trace(&flat, &holeidxs, dims, &opts);
+        try!(writeln!(
+            &f,
+            r###"testOutput["{}"]["trace"]={};"###,
+            filename,
+            earcutr::trace_json(&trace)
+        ));
+    }
     dlog!(4, "wrote results to {}", outfile);
     Ok(())
 }
 
+fn load_fixture(filename: &str) -> Vec<Vec<Vec<f64>>> {
+    load_file(&format!("tests/fixtures/{}.json", filename))
+}
+
+fn load_file(fullname: &str) -> Vec<Vec<Vec<f64>>> {
+    let mut strdata = String::new();
+    match File::open(fullname) {
+        Err(why) => panic!("failed to open file '{}': {}", fullname, why),
+        Ok(mut f) => f.read_to_string(&mut strdata).unwrap(),
+    };
+    parse_json(strdata.trim()).unwrap()
+}
+
 // verify if triangles cover the same area as the shape itself
 fn area_test(filename: &str, expected_num_tris: usize, expected_deviation: f64) -> bool {
     //    let visualize = std::env::args().any(|x| x == "--test-threads=1");
@@ -194,8 +223,810 @@ fn test_empty() {
     assert!(indices.len() == 0);
 }
 
+#[test]
+fn test_strict_simple() {
+    let data = vec![10.0, 0.0, 0.0, 50.0, 60.0, 60.0, 70.0, 10.0];
+    let opts = earcutr::EarcutOptions::new().strict(true);
+    let indices = earcutr::earcut_with_options(&data, &vec![], 2, &opts);
+    assert!(indices == Ok(earcutr::earcut(&data, &vec![], 2)));
+}
+
+#[test]
+fn test_strict_self_intersection() {
+    // bowtie. the default mode heals it into a single triangle
+    let data = vec![0.0, 0.0, 2.0, 2.0, 2.0, 0.0, 0.0, 2.0];
+    let opts = earcutr::EarcutOptions::new().strict(true);
+    match earcutr::earcut_with_options(&data, &vec![], 2, &opts) {
+        Err(earcutr::EarcutError::Unclipped { ring }) => assert!(ring.len() == 3),
+        r => panic!("expected unclipped ring, got {:?}", r),
+    }
+}
+
+#[test]
+fn test_strict_no_hole_bridge() {
+    // the hole lies outside, to the left of the outer ring
+    let data = vec![
+        10.0, 0.0, 20.0, 0.0, 20.0, 10.0, 10.0, 10.0, 1.0, 1.0, 2.0, 1.0, 2.0, 2.0,
+    ];
+    let opts = earcutr::EarcutOptions::new().strict(true);
+    match earcutr::earcut_with_options(&data, &vec![4], 2, &opts) {
+        Err(earcutr::EarcutError::NoHoleBridge { hole, outer }) => {
+            assert!(hole.len() == 3 && outer.len() == 4)
+        }
+        r => panic!("expected missing hole bridge, got {:?}", r),
+    }
+}
+
+#[test]
+fn test_bad_input() {
+    use earcutr::EarcutError::*;
+    let opts = earcutr::EarcutOptions::new();
+    let cut = |data: &Vec<f64>, holes: &Vec<usize>| earcutr::earcut_with_options(data, holes, 2, &opts);
+    let square = vec![0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0];
+    assert!(cut(&vec![], &vec![]) == Ok(vec![]));
+    assert!(cut(&vec![1.0], &vec![]) == Ok(vec![]));
+    assert!(cut(&vec![], &vec![0, 3]) == Err(BadHoleIndex { hole: 1 }));
+    // a coordinate left over at the end is ignored
+    let mut odd = square.clone();
+    odd.push(5.0);
+    assert!(cut(&odd, &vec![]) == cut(&square, &vec![]));
+    assert!(cut(&odd, &vec![4]) == cut(&square, &vec![]));
+    assert!(earcutr::earcut_with_options(&odd, &vec![], 3, &opts) == Ok(vec![]));
+
+    // holes past the end, or out of order
+    assert!(cut(&square, &vec![5]) == Err(BadHoleIndex { hole: 0 }));
+    assert!(cut(&square, &vec![3, 2]) == Err(BadHoleIndex { hole: 1 }));
+    assert!(earcutr::earcut(&square, &vec![3, 2], 2).is_empty());
+    // empty rings are no trouble
+    let triangles = cut(&square, &vec![]).unwrap();
+    assert!(cut(&square, &vec![4, 4]) == Ok(triangles.clone()));
+    // no outer ring, so nothing to triangulate
+    assert!(cut(&square, &vec![0]) == Ok(vec![]));
+    // a hole that can't be bridged is left out
+    let mut data = square.clone();
+    data.extend(vec![-5.0, 1.0, -4.0, 1.0, -4.0, 2.0]);
+    let cut_out = cut(&data, &vec![4]).unwrap();
+    assert!(cut_out.len() == triangles.len() && cut_out.iter().all(|&i| i < 4));
+
+    // filtering after the first bridge leaves one node of the outer ring;
+    // this used to loop forever
+    let a = 5.267982718356894e298;
+    let data = vec![a, 0.0, 0.0, 1.0, 1.0, 1.0, a, 0.0, 0.0, 1.0, a, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 6.0];
+    assert!(cut(&data, &vec![3, 5]) == Ok(vec![]));
+
+    for &bad in &[f64::NAN, f64::INFINITY, -f64::INFINITY] {
+        let mut data = square.clone();
+        data[5] = bad;
+        assert!(cut(&data, &vec![]) == Err(OutOfRange { vertex: 2 }));
+    }
+}
+
+#[test]
+fn test_integer_coords() {
+    let data = vec![0, 0, 100, 0, 100, 100, 0, 100, 20, 20, 80, 20, 80, 80, 20, 80];
+    let fdata: Vec<f64> = data.iter().map(|&c| c as f64).collect();
+    let expected = earcutr::earcut(&fdata, &vec![4], 2);
+    let opts = earcutr::EarcutOptions::new();
+    let data64: Vec<i64> = data.iter().map(|&c| c as i64).collect();
+    assert!(earcutr::earcut_with_options(&data, &vec![4], 2, &opts) == Ok(expected.clone()));
+    assert!(earcutr::earcut_with_options(&data64, &vec![4], 2, &opts) == Ok(expected));
+}
+
+#[test]
+fn test_integer_range() {
+    let big = 1i64 << 52;
+    let data = vec![-big, -big, big, -big, big, big, -big, big];
+    let opts = earcutr::EarcutOptions::new();
+    let triangles = earcutr::earcut_with_options(&data, &vec![], 2, &opts).unwrap();
+    assert!(triangles.len() == 6);
+    let data = vec![0, 0, big * 2, 0, 0, 1];
+    assert!(
+        earcutr::earcut_with_options(&data, &vec![], 2, &opts)
+            == Err(earcutr::EarcutError::OutOfRange { vertex: 1 })
+    );
+    // i64::MIN has no absolute value as an i64
+    let data = vec![0, 0, 1, 0, 0, i64::MIN];
+    assert!(
+        earcutr::earcut_with_options(&data, &vec![], 2, &opts)
+            == Err(earcutr::EarcutError::OutOfRange { vertex: 2 })
+    );
+}
+
+#[test]
+fn test_integer_fixture() {
+    // water3 rounded to whole units, hashed path
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("water3"));
+    let idata: Vec<i32> = data.iter().map(|c| c.round() as i32).collect();
+    let fdata: Vec<f64> = idata.iter().map(|&c| c as f64).collect();
+    let opts = earcutr::EarcutOptions::new();
+    let triangles = earcutr::earcut_with_options(&idata, &holeidxs, dims, &opts).unwrap();
+    assert!(triangles.len() / 3 == 197);
+    assert!(earcutr::deviation(&fdata, &holeidxs, dims, &triangles) == 0.0);
+}
+
 // file based tests
 
+#[test]
+fn test_robust_fixtures() {
+    // ordinary data should not notice the exact predicates
+    let opts = earcutr::EarcutOptions::new().robust(true);
+    for name in &["water", "water3b", "hilbert", "self-touching", "eberly-6"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let triangles = earcutr::earcut_with_options(&data, &holeidxs, dims, &opts).unwrap();
+        assert!(triangles == earcutr::earcut(&data, &holeidxs, dims));
+    }
+}
+
+#[test]
+fn test_translation_invariant() {
+    // fixtures on a fine grid, moved far from the origin. the move is
+    // exact and the local frame undoes it, so the triangles must not change
+    for name in &["building", "dude", "hole-touching-outer", "bad-hole", "issue34"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let small: Vec<f64> = data.iter().map(|c| c.round() * 2f64.powi(-11)).collect();
+        let moved: Vec<f64> = small.iter().map(|c| c + 2f64.powi(41)).collect();
+        let triangles = earcutr::earcut(&small, &holeidxs, dims);
+        assert!(earcutr::earcut(&moved, &holeidxs, dims) == triangles);
+    }
+}
+
+#[test]
+fn test_weld() {
+    // vertex 4 is a hair away from vertex 3; welded, the sliver between
+    // them disappears and only one of the two is used
+    let data = vec![0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 1e-12, 10.0, 0.0, 10.0];
+    let plain = earcutr::earcut(&data, &vec![], 2);
+    assert!(plain.len() == 9);
+    let opts = earcutr::EarcutOptions::new().weld(1e-9);
+    let welded = earcutr::earcut_with_options(&data, &vec![], 2, &opts).unwrap();
+    assert!(welded.len() == 6);
+    assert!(welded.contains(&3) != welded.contains(&4));
+    let opts = earcutr::EarcutOptions::new().snap_to_grid(1e-6);
+    let snapped = earcutr::earcut_with_options(&data, &vec![], 2, &opts).unwrap();
+    assert!(snapped.len() == 6);
+}
+
+#[test]
+fn test_default_knobs() {
+    // spelling out the defaults, or forcing the hash either way, must not
+    // change anything; the hash only speeds up finding points near an ear
+    let defaults = earcutr::EarcutOptions::new()
+        .hash_threshold(40)
+        .zorder_bits(15)
+        .skip_after_ear(true)
+        .filter_pass(true)
+        .cure_pass(true)
+        .split_pass(true);
+    let hashed = earcutr::EarcutOptions::new().hashed(true).zorder_bits(4);
+    let unhashed = earcutr::EarcutOptions::new().hashed(false);
+    for name in &["building", "dude", "water3", "touching-holes", "hilbert"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let triangles = earcutr::earcut(&data, &holeidxs, dims);
+        for opts in &[&defaults, &hashed, &unhashed] {
+            assert!(earcutr::earcut_with_options(&data, &holeidxs, dims, opts).unwrap() == triangles);
+        }
+    }
+}
+
+#[test]
+fn test_skip_after_ear() {
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("building"));
+    let opts = earcutr::EarcutOptions::new().skip_after_ear(false);
+    let triangles = earcutr::earcut_with_options(&data, &holeidxs, dims, &opts).unwrap();
+    assert!(triangles.len() / 3 == 13);
+    assert!(triangles != earcutr::earcut(&data, &holeidxs, dims));
+    assert!(earcutr::deviation(&data, &holeidxs, dims, &triangles) == 0.0);
+}
+
+#[test]
+fn test_best_ears() {
+    // the triangles with an angle under 5 degrees
+    let slivers = |data: &Vec<f64>, triangles: &Vec<usize>| {
+        let sliver = |t: &[usize]| {
+            (0..3).any(|i| {
+                let (a, b, c) = (2 * t[i], 2 * t[(i + 1) % 3], 2 * t[(i + 2) % 3]);
+                let (ux, uy) = (data[b] - data[a], data[b + 1] - data[a + 1]);
+                let (vx, vy) = (data[c] - data[a], data[c + 1] - data[a + 1]);
+                (ux * vy - uy * vx).abs().atan2(ux * vx + uy * vy).to_degrees() < 5.0
+            })
+        };
+        triangles.chunks(3).filter(|t| sliver(t)).count()
+    };
+    let best = earcutr::EarcutOptions::new().best_ears(true);
+    for name in &["water", "eberly-6", "dude", "water3", "hilbert"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let triangles = earcutr::earcut(&data, &holeidxs, dims);
+        let deviation = earcutr::deviation(&data, &holeidxs, dims, &triangles);
+        for opts in &[best.clone().hashed(true), best.clone().hashed(false)] {
+            let (result, stats) = earcutr::earcut_with_stats(&data, &holeidxs, dims, opts);
+            let result = result.unwrap();
+            assert!(result.len() == triangles.len() && stats.ears[0] > 0, "{}", name);
+            assert!(earcutr::deviation(&data, &holeidxs, dims, &result) <= deviation + 1e-12);
+            assert!(slivers(&data, &result) < slivers(&data, &triangles), "{}", name);
+        }
+    }
+    // shared-points has no ear at all, and has to be split where earcut
+    // splits it
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("shared-points"));
+    let triangles = earcutr::earcut_with_options(&data, &holeidxs, dims, &best).unwrap();
+    assert!(triangles.len() / 3 == 4 && earcutr::deviation(&data, &holeidxs, dims, &triangles) == 0.0);
+}
+
+#[test]
+fn test_optimal() {
+    // the smallest and largest angle, in radians, and the total length of
+    // the triangles' edges
+    let measure = |data: &Vec<f64>, triangles: &Vec<usize>| {
+        let (mut min, mut max, mut length) = (std::f64::consts::PI, 0f64, 0.0);
+        for t in triangles.chunks(3) {
+            for i in 0..3 {
+                let (a, b, c) = (2 * t[i], 2 * t[(i + 1) % 3], 2 * t[(i + 2) % 3]);
+                let (ux, uy) = (data[b] - data[a], data[b + 1] - data[a + 1]);
+                let (vx, vy) = (data[c] - data[a], data[c + 1] - data[a + 1]);
+                let angle = (ux * vy - uy * vx).abs().atan2(ux * vx + uy * vy);
+                min = min.min(angle);
+                max = max.max(angle);
+                length += ux.hypot(uy);
+            }
+        }
+        (min, max, length)
+    };
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("building"));
+    let triangles = earcutr::earcut(&data, &holeidxs, dims);
+    let (min, max, length) = measure(&data, &triangles);
+    for &objective in &[earcutr::Objective::MaxMinAngle, earcutr::Objective::MinMaxAngle, earcutr::Objective::MinWeight] {
+        let opts = earcutr::EarcutOptions::new().optimal(objective);
+        let (result, stats) = earcutr::earcut_with_stats(&data, &holeidxs, dims, &opts);
+        let result = result.unwrap();
+        assert!(stats.optimal == 1 && result.len() == triangles.len());
+        assert!(earcutr::deviation(&data, &holeidxs, dims, &result) == 0.0);
+        let better = match objective {
+            earcutr::Objective::MaxMinAngle => measure(&data, &result).0 > min,
+            earcutr::Objective::MinMaxAngle => measure(&data, &result).1 < max,
+            earcutr::Objective::MinWeight => measure(&data, &result).2 < length,
+        };
+        assert!(better, "{:?}", objective);
+        // too large for it
+        let opts = opts.hashed(true);
+        assert!(earcutr::earcut_with_options(&data, &holeidxs, dims, &opts).unwrap() == triangles);
+    }
+    // holes, and a ring that touches itself, are ear clipped
+    let opts = earcutr::EarcutOptions::new().optimal(earcutr::Objective::MinWeight);
+    for name in &["issue45", "bad-diagonals"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let (result, stats) = earcutr::earcut_with_stats(&data, &holeidxs, dims, &opts);
+        assert!(stats.optimal == 0 && result.unwrap() == earcutr::earcut(&data, &holeidxs, dims));
+    }
+    // nor are polygons of more than 50 vertices, even unhashed
+    let unhashed = earcutr::EarcutOptions::new().hashed(false);
+    for &(n, optimal) in &[(50, 1), (51, 0), (2000, 0)] {
+        let (data, holeidxs, dims) = earcutr::flatten(&earcutr::generate::star(n as u64, n));
+        let (result, stats) = earcutr::earcut_with_stats(&data, &holeidxs, dims, &opts.clone().hashed(false));
+        let result = result.unwrap();
+        assert!(stats.optimal == optimal && result.len() == 3 * (n - 2), "{}", n);
+        if optimal == 0 {
+            assert!(result == earcutr::earcut_with_options(&data, &holeidxs, dims, &unhashed).unwrap(), "{}", n);
+        }
+    }
+}
+
+#[test]
+fn test_fallback_passes() {
+    // touching-holes needs split_earcut for most of its triangles
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("touching-holes"));
+    let opts = earcutr::EarcutOptions::new().split_pass(false);
+    let triangles = earcutr::earcut_with_options(&data, &holeidxs, dims, &opts).unwrap();
+    assert!(triangles.len() / 3 == 19);
+    let opts = earcutr::EarcutOptions::new().filter_pass(false);
+    let triangles = earcutr::earcut_with_options(&data, &holeidxs, dims, &opts).unwrap();
+    assert!(triangles.len() / 3 == 56);
+}
+
+#[test]
+fn test_budget() {
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("water-huge"));
+    let full = earcutr::earcut(&data, &holeidxs, dims);
+    let opts = earcutr::EarcutOptions::new().max_operations(1000);
+    match earcutr::earcut_with_options(&data, &holeidxs, dims, &opts) {
+        Err(earcutr::EarcutError::OverBudget { triangles }) => {
+            assert!(!triangles.is_empty() && triangles.len() < full.len());
+            assert!(triangles[..] == full[..triangles.len()]);
+        }
+        _ => panic!("expected OverBudget"),
+    }
+    let opts = earcutr::EarcutOptions::new().max_operations(1_000_000);
+    assert!(earcutr::earcut_with_options(&data, &holeidxs, dims, &opts).unwrap() == full);
+    let opts = earcutr::EarcutOptions::new().deadline(std::time::Instant::now());
+    match earcutr::earcut_with_options(&data, &holeidxs, dims, &opts) {
+        Err(earcutr::EarcutError::OverBudget { .. }) => {}
+        _ => panic!("expected OverBudget"),
+    }
+}
+
+#[test]
+fn test_cancel() {
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("water-huge"));
+    let token = earcutr::CancelToken::new();
+    let opts = earcutr::EarcutOptions::new().cancel_token(&token);
+    assert!(earcutr::earcut_with_options(&data, &holeidxs, dims, &opts).is_ok());
+    token.clone().cancel();
+    assert!(token.is_cancelled());
+    match earcutr::earcut_with_options(&data, &holeidxs, dims, &opts) {
+        Err(earcutr::EarcutError::Cancelled { .. }) => {}
+        _ => panic!("expected Cancelled"),
+    }
+}
+
+#[test]
+fn test_small_stack() {
+    // the worker threads of a tile renderer may have small stacks
+    for name in &["water-huge", "water-huge2", "touching-holes"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let triangles = earcutr::earcut(&data, &holeidxs, dims);
+        let worker = std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(move || earcutr::earcut(&data, &holeidxs, dims))
+            .unwrap();
+        assert!(worker.join().unwrap() == triangles);
+    }
+}
+
+#[test]
+fn test_stats() {
+    let opts = earcutr::EarcutOptions::new();
+    for name in &["water-huge", "touching-holes", "dude"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let (result, stats) = earcutr::earcut_with_stats(&data, &holeidxs, dims, &opts);
+        let triangles = result.unwrap();
+        assert!(triangles == earcutr::earcut(&data, &holeidxs, dims));
+        assert!(stats.nodes == data.len() / dims);
+        // water-huge has a hole no bridge is found for
+        assert!(stats.holes <= holeidxs.len());
+        assert!(stats.bridge_nodes == 2 * (stats.holes + stats.splits));
+        let ears: usize = stats.ears.iter().sum();
+        assert!(ears + stats.cure_fixes == triangles.len() / 3);
+        assert!(stats.clip_time > std::time::Duration::from_secs(0));
+    }
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("touching-holes"));
+    let (_, stats) = earcutr::earcut_with_stats(&data, &holeidxs, dims, &opts);
+    assert!(stats.holes == holeidxs.len());
+    assert!(stats.splits == 6);
+}
+
+#[cfg(feature = "trace")]
+#[test]
+fn test_trace() {
+    use earcutr::TraceEvent;
+    let opts = earcutr::EarcutOptions::new();
+    for name in &["water-huge", "touching-holes", "dude"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let (result, trace) = earcutr::earcut_with_trace(&data, &holeidxs, dims, &opts);
+        let triangles = result.unwrap();
+        // the clipped ears, in order, are the triangles
+        let mut clipped = Vec::new();
+        for event in &trace {
+            match *event {
+                TraceEvent::Ear { a, b, c } | TraceEvent::Cure { a, b, c } => {
+                    clipped.extend_from_slice(&[a, b, c])
+                }
+                _ => {}
+            }
+        }
+        assert!(clipped == triangles);
+        let count = |f: &dyn Fn(&TraceEvent) -> bool| trace.iter().filter(|e| f(e)).count();
+        assert!(count(&|e| matches!(e, TraceEvent::Ring { .. })) == holeidxs.len() + 1);
+        // a hole no bridge is found for isn't joined to the outer ring
+        let (_, stats) = earcutr::earcut_with_stats(&data, &holeidxs, dims, &opts);
+        assert!(count(&|e| matches!(e, TraceEvent::Bridge { .. })) == stats.holes);
+        let json: serde_json::Value = serde_json::from_str(&earcutr::trace_json(&trace)).unwrap();
+        assert!(json.as_array().unwrap().len() == trace.len());
+    }
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("touching-holes"));
+    let (_, trace) = earcutr::earcut_with_trace(&data, &holeidxs, dims, &opts);
+    assert!(trace.iter().any(|e| matches!(e, TraceEvent::Split { .. })));
+    assert!(trace.iter().any(|e| match *e {
+        TraceEvent::NotEar { blocker, .. } => blocker.is_some(),
+        _ => false,
+    }));
+}
+
+#[test]
+fn test_verify() {
+    for name in &["building", "dude", "hilbert", "issue35", "touching-holes", "water2"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let triangles = earcutr::earcut(&data, &holeidxs, dims);
+        let v = earcutr::verify(&data, &holeidxs, dims, &triangles);
+        assert!(v.is_valid());
+        assert!(v.triangles == triangles.len() / 3);
+    }
+    // deviation is small, but some triangles overlap and some area is missed
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("water"));
+    let triangles = earcutr::earcut(&data, &holeidxs, dims);
+    let v = earcutr::verify(&data, &holeidxs, dims, &triangles);
+    assert!(!v.is_valid());
+    assert!(!v.overlaps.is_empty() && !v.uncovered.is_empty() && !v.excess.is_empty());
+    assert!(v.overlaps.iter().all(|o| o.a < o.b && o.region.len() >= 3));
+
+    let square = vec![0, 0, 10, 0, 10, 10, 0, 10];
+    let triangles = earcutr::earcut_with_options(&square, &vec![], 2, &Default::default());
+    let v = earcutr::verify(&square, &[], 2, &triangles.unwrap());
+    assert!(v.is_valid() && v.triangles == v.expected);
+
+    // one dimension has no points to look at
+    let v = earcutr::verify(&[0.0, 1.0, 2.0], &[], 1, &[0, 1, 2]);
+    assert!(!v.is_valid() && v.bad_indices == vec![0]);
+}
+
+#[test]
+fn test_diagnostics() {
+    let opts = earcutr::EarcutOptions::new();
+    let diagnose = |name: &str| {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let (triangles, d) = earcutr::earcut_with_diagnostics(&data, &holeidxs, dims, &opts);
+        let triangles = triangles.unwrap();
+        assert!(triangles == earcutr::earcut(&data, &holeidxs, dims));
+        // exactly the vertices that are in no triangle are listed
+        for i in 0..data.len() / dims {
+            let dropped = d.dropped.iter().any(|&(v, _)| v == i);
+            assert!(dropped != triangles.contains(&i));
+        }
+        d
+    };
+    use earcutr::Dropped::*;
+    let d = diagnose("steiner");
+    assert!(d.dropped.is_empty() && d.steiner == vec![4, 5, 6, 7]);
+    let d = diagnose("empty-square");
+    assert!(d.discarded_rings == vec![(0, DegenerateRing), (1, DegenerateRing)]);
+    let d = diagnose("degenerate");
+    assert!(d.discarded_rings == vec![(0, DegenerateRing)]);
+    assert!(d.dropped.iter().any(|&(_, w)| w == Collinear));
+    let d = diagnose("issue83");
+    assert!(d.discarded_rings == vec![(0, DegenerateRing), (1, DegenerateRing), (2, NotBridged)]);
+    let d = diagnose("water");
+    assert!(d.discarded_rings.is_empty());
+    assert!(d.dropped.iter().any(|&(_, w)| w == SelfIntersection));
+    assert!(d.dropped.iter().any(|&(_, w)| w == Duplicate));
+    let d = diagnose("water-huge");
+    assert!(d.discarded_rings.iter().any(|&(_, w)| w == NotBridged));
+}
+
+#[test]
+fn test_steiner_points() {
+    use earcutr::Rejected::*;
+    let opts = earcutr::EarcutOptions::new();
+    // the steiner fixture, with the points given as such rather than as
+    // holes of one vertex, gives the same triangles
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("steiner"));
+    let (outer, points) = data.split_at(holeidxs[0] * dims);
+    let (triangles, rejected) = earcutr::earcut_with_steiner(&outer.to_vec(), &vec![], dims, points, &opts);
+    assert!(triangles == Ok(earcutr::earcut(&data, &holeidxs, dims)) && rejected.is_empty());
+
+    let square = vec![0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0, 2.0, 2.0, 2.0, 4.0, 4.0, 4.0, 4.0, 2.0];
+    // inside, so vertex 8; in the hole; outside; on an edge; on a vertex;
+    // the same as the first; inside, so vertex 14; NaN
+    let points = vec![5.0, 5.0, 3.0, 3.0, 20.0, 5.0, 10.0, 5.0, 0.0, 10.0, 5.0, 5.0, 7.0, 8.0, f64::NAN, 1.0];
+    let (triangles, rejected) = earcutr::earcut_with_steiner(&square, &vec![4], 2, &points, &opts);
+    let triangles = triangles.unwrap();
+    assert!(rejected == vec![(1, Outside), (2, Outside), (3, OnBoundary), (4, OnBoundary), (5, Duplicate), (7, Outside)]);
+    assert!(triangles.contains(&8) && triangles.contains(&14));
+    assert!(triangles.iter().all(|&i| i <= 8 || i == 14));
+    // two more vertices, each two more triangles
+    assert!(triangles.len() / 3 == 8 + 2 * 2);
+    // and cover the polygon, the points being holes of one vertex
+    let mut all = square.clone();
+    all.extend(&points[..7 * 2]);
+    assert!(earcutr::deviation(&all, &vec![4, 8, 9, 10, 11, 12, 13, 14], 2, &triangles) == 0.0);
+
+    // integer coordinates, and errors about the polygon
+    let (triangles, rejected) = earcutr::earcut_with_steiner(&vec![0, 0, 4, 0, 0, 4], &vec![], 2, &[1, 1], &opts);
+    assert!(triangles.unwrap().len() == 9 && rejected.is_empty());
+    // a coordinate without a partner is ignored
+    let (odd, rejected) = earcutr::earcut_with_steiner(&vec![0, 0, 4, 0, 0, 4], &vec![], 2, &[1, 1, 3], &opts);
+    assert!(odd.unwrap().len() == 9 && rejected.is_empty());
+    let (triangles, rejected) = earcutr::earcut_with_steiner(&square, &vec![9], 2, &[5.0, 5.0], &opts);
+    assert!(triangles == Err(earcutr::EarcutError::BadHoleIndex { hole: 0 }) && rejected.is_empty());
+}
+
+#[test]
+fn test_constraints() {
+    use earcutr::Rejected::*;
+    let opts = earcutr::EarcutOptions::new();
+    let square = vec![0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0, 2.0, 2.0, 2.0, 4.0, 4.0, 4.0, 4.0, 2.0];
+    let polylines = vec![
+        vec![1.0, 5.0, 5.0, 5.0, 5.0, 8.0], // vertices 8, 9, 10
+        vec![9.0, 1.0, 9.0, 9.0],           // 11, 12
+        vec![20.0, 5.0, 5.0, 1.0],          // outside
+        vec![0.0, 5.0, 2.0, 7.0],           // from an edge, 15, 16
+        vec![5.0, 5.0, 6.0, 1.0],           // the same point as the first
+        vec![3.0, 1.0, 3.0, 6.0],           // through the hole
+        vec![2.0, 4.5, 2.0, 6.0],           // across the first
+        vec![7.0, 7.0],                     // a steiner point, 23
+    ];
+    let (triangles, rejected) = earcutr::earcut_with_constraints(&square, &vec![4], 2, &polylines, &opts);
+    let triangles = triangles.unwrap();
+    assert!(rejected == vec![(2, Outside), (4, Duplicate), (5, Crosses), (6, Crosses)]);
+    assert!(triangles.iter().all(|&i| i <= 12 || i == 15 || i == 16 || i == 23));
+    assert!((8..13).chain(15..17).chain(23..24).all(|i| triangles.contains(&i)));
+    // two triangles for each point inside, one for each on the rings
+    assert!(triangles.len() / 3 == 8 + 2 * 7 + 1);
+    // every segment is an edge of a triangle
+    for &(a, b) in &[(8, 9), (9, 10), (11, 12), (15, 16)] {
+        assert!(triangles.chunks(3).any(|t| t.contains(&a) && t.contains(&b)));
+    }
+    // and the triangles cover the polygon, the points being holes of one
+    // vertex
+    let mut all = square.clone();
+    all.extend(polylines.iter().flatten());
+    let holes: Vec<usize> = (4..5).chain(8..24).collect();
+    assert!(earcutr::deviation(&all, &holes, 2, &triangles) == 0.0);
+
+    // lines from ring to ring cut the polygon: across the square, corner
+    // to corner, and in two parts, through a hole. no triangle is on both
+    // sides of the line
+    let square = vec![0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0];
+    let with_hole = [&square[..], &[4.0, 4.0, 6.0, 4.0, 6.0, 6.0, 4.0, 6.0]].concat();
+    let cases = vec![
+        (&square, vec![], vec![vec![0.0, 4.0, 10.0, 6.0]], 4),
+        (&square, vec![], vec![vec![0.0, 0.0, 10.0, 10.0]], 2),
+        (&with_hole, vec![4], vec![vec![0.0, 5.0, 4.0, 5.0], vec![6.0, 5.0, 10.0, 5.0]], 12),
+    ];
+    for (data, holes, lines, count) in cases {
+        let (triangles, rejected) = earcutr::earcut_with_constraints(data, &holes, 2, &lines, &opts);
+        let triangles = triangles.unwrap();
+        assert!(rejected.is_empty() && triangles.len() / 3 == count);
+        let mut all = data.clone();
+        all.extend(lines.iter().flatten());
+        let (p, q) = ([lines[0][0], lines[0][1]], [lines[lines.len() - 1][2], lines[lines.len() - 1][3]]);
+        let side = |&i: &usize| (q[0] - p[0]) * (all[2 * i + 1] - p[1]) - (q[1] - p[1]) * (all[2 * i] - p[0]);
+        assert!(triangles.chunks(3).all(|t| t.iter().all(|i| side(i) >= 0.0) || t.iter().all(|i| side(i) <= 0.0)));
+        let points: Vec<usize> = (data.len() / 2..all.len() / 2).collect();
+        assert!(earcutr::deviation(&all, &[&holes[..], &points[..]].concat(), 2, &triangles) == 0.0);
+    }
+
+    // no polylines, integer coordinates, and errors about the polygon
+    let (triangles, rejected) = earcutr::earcut_with_constraints(&square, &vec![4], 2, &[], &opts);
+    assert!(triangles == Ok(earcutr::earcut(&square, &vec![4], 2)) && rejected.is_empty());
+    let (triangles, rejected) = earcutr::earcut_with_constraints(&vec![0, 0, 9, 0, 0, 9], &vec![], 2, &[vec![1, 1, 3, 2]], &opts);
+    assert!(triangles.unwrap().len() == 3 * 5 && rejected.is_empty());
+    let (odd, rejected) = earcutr::earcut_with_constraints(&vec![0, 0, 9, 0, 0, 9], &vec![], 2, &[vec![1, 1, 3, 2, 5]], &opts);
+    assert!(odd.unwrap().len() == 3 * 5 && rejected.is_empty());
+    let (triangles, rejected) = earcutr::earcut_with_constraints(&square, &vec![9], 2, &polylines, &opts);
+    assert!(triangles == Err(earcutr::EarcutError::BadHoleIndex { hole: 0 }) && rejected.is_empty());
+}
+
+#[test]
+fn test_delaunay() {
+    // the smallest angle of any triangle, in radians
+    let min_angle = |data: &Vec<f64>, triangles: &Vec<usize>| {
+        let mut min = std::f64::consts::PI;
+        for t in triangles.chunks(3) {
+            for i in 0..3 {
+                let (a, b, c) = (2 * t[i], 2 * t[(i + 1) % 3], 2 * t[(i + 2) % 3]);
+                let (ux, uy) = (data[b] - data[a], data[b + 1] - data[a + 1]);
+                let (vx, vy) = (data[c] - data[a], data[c + 1] - data[a + 1]);
+                min = min.min((ux * vy - uy * vx).abs().atan2(ux * vx + uy * vy));
+            }
+        }
+        min
+    };
+    let opts = earcutr::EarcutOptions::new().delaunay(true);
+    for name in &["water", "dude", "hole-touching-outer", "steiner"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let clipped = earcutr::earcut(&data, &holeidxs, dims);
+        let (result, stats) = earcutr::earcut_with_stats(&data, &holeidxs, dims, &opts);
+        let flipped = result.unwrap();
+        assert!(stats.flips > 0 && flipped.len() == clipped.len());
+        // the same every time
+        for _ in 0..4 {
+            assert!(earcutr::earcut_with_options(&data, &holeidxs, dims, &opts) == Ok(flipped.clone()));
+        }
+        let deviation = earcutr::deviation(&data, &holeidxs, dims, &clipped);
+        assert!((earcutr::deviation(&data, &holeidxs, dims, &flipped) - deviation).abs() < 1e-12);
+        assert!(min_angle(&data, &flipped) > min_angle(&data, &clipped));
+        // the same vertices, and the same edges along the rings
+        let vertices = |triangles: &Vec<usize>| {
+            let mut v = triangles.clone();
+            v.sort_unstable();
+            v.dedup();
+            v
+        };
+        assert!(vertices(&flipped) == vertices(&clipped));
+        let edges = |triangles: &Vec<usize>| -> std::collections::HashSet<(usize, usize)> {
+            triangles.chunks(3).flat_map(|t| (0..3).map(move |i| (t[i], t[(i + 1) % 3]))).collect()
+        };
+        let (flipped, clipped) = (edges(&flipped), edges(&clipped));
+        let mut bounds = vec![0];
+        bounds.extend(&holeidxs);
+        bounds.push(data.len() / dims);
+        for ring in bounds.windows(2) {
+            for a in ring[0]..ring[1] {
+                let b = if a + 1 < ring[1] { a + 1 } else { ring[0] };
+                for &(a, b) in &[(a, b), (b, a)] {
+                    assert!(flipped.contains(&(a, b)) == clipped.contains(&(a, b)), "{} {} {}", name, a, b);
+                }
+            }
+        }
+    }
+    // nothing to flip in a square
+    let square = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+    let (result, stats) = earcutr::earcut_with_stats(&square, &vec![], 2, &opts);
+    assert!(result == Ok(earcutr::earcut(&square, &vec![], 2)) && stats.flips == 0);
+}
+
+#[test]
+fn test_refinement() {
+    // the smallest angle, in degrees, and the largest area of the triangles
+    let quality = |data: &Vec<f64>, triangles: &Vec<usize>| {
+        let (mut angle, mut area) = (180f64, 0f64);
+        for t in triangles.chunks(3) {
+            for i in 0..3 {
+                let (a, b, c) = (2 * t[i], 2 * t[(i + 1) % 3], 2 * t[(i + 2) % 3]);
+                let (ux, uy) = (data[b] - data[a], data[b + 1] - data[a + 1]);
+                let (vx, vy) = (data[c] - data[a], data[c + 1] - data[a + 1]);
+                angle = angle.min((ux * vy - uy * vx).abs().atan2(ux * vx + uy * vy).to_degrees());
+                area = area.max((ux * vy - uy * vx) / 2.0);
+            }
+        }
+        (angle, area)
+    };
+    let opts = earcutr::EarcutOptions::new();
+    let refinement = earcutr::Refinement::new().min_angle(20.0);
+    for name in &["building", "issue45", "water3b"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let n = data.len() / dims;
+        let (triangles, vertices) = earcutr::earcut_with_refinement(&data, &holeidxs, dims, &refinement, &opts);
+        let triangles = triangles.unwrap();
+        assert!(vertices.len() > data.len() && vertices[..data.len()] == data[..]);
+        assert!(triangles.iter().all(|&i| i < vertices.len() / 2));
+        assert!(quality(&vertices, &triangles).0 >= 20.0, "{}", name);
+        // the same every time
+        for _ in 0..4 {
+            let again = earcutr::earcut_with_refinement(&data, &holeidxs, dims, &refinement, &opts);
+            assert!(again == (Ok(triangles.clone()), vertices.clone()), "{}", name);
+        }
+        // the triangles cover the polygon, the added vertices being holes
+        // of one vertex
+        let mut holes = holeidxs.clone();
+        holes.extend(n..vertices.len() / 2);
+        assert!(earcutr::deviation(&vertices, &holes, 2, &triangles) < 1e-12);
+    }
+
+    // a square, only by area
+    let square = vec![0.0, 0.0, 10.0, 0.0, 10.0, 10.0, 0.0, 10.0];
+    let refinement = earcutr::Refinement::new().max_area(1.0);
+    let (triangles, vertices) = earcutr::earcut_with_refinement(&square, &vec![], 2, &refinement, &opts);
+    let triangles = triangles.unwrap();
+    assert!(quality(&vertices, &triangles).1 <= 1.0 && triangles.len() / 3 >= 100);
+    let holes: Vec<usize> = (4..vertices.len() / 2).collect();
+    assert!(earcutr::deviation(&vertices, &holes, 2, &triangles) < 1e-12);
+    // a ring that crosses itself, and gets a flat triangle
+    let crossed = vec![3.0, 5.0, 9.0, 4.0, 0.0, 9.0, 6.0, 1.0, 2.0, 0.0, 3.0, 8.0, 7.0, 6.0, 8.0, 9.0];
+    let (triangles, vertices) = earcutr::earcut_with_refinement(&crossed, &vec![], 2, &refinement, &opts);
+    let flipped = earcutr::earcut_with_options(&crossed, &vec![], 2, &opts.clone().delaunay(true)).unwrap();
+    assert!(earcutr::quality_report(&crossed, 2, &flipped).degenerate == 1);
+    assert!(triangles == Err(earcutr::EarcutError::Unrefined { triangles: flipped }) && vertices == crossed);
+    // out of points, the triangles still cover it
+    let (triangles, vertices) = earcutr::earcut_with_refinement(&square, &vec![], 2, &refinement.clone().max_points(5), &opts);
+    let triangles = match triangles {
+        Err(earcutr::EarcutError::OverBudget { triangles }) => triangles,
+        _ => panic!("expected OverBudget"),
+    };
+    assert!(vertices.len() == 2 * (4 + 5));
+    assert!(earcutr::deviation(&vertices, &(4..9).collect(), 2, &triangles) < 1e-12);
+    // no refinement asked for
+    let (triangles, vertices) = earcutr::earcut_with_refinement(&square, &vec![], 2, &earcutr::Refinement::new(), &opts);
+    assert!(triangles.unwrap().len() == 6 && vertices == square);
+    let (triangles, vertices) = earcutr::earcut_with_refinement(&square, &vec![7], 2, &refinement, &opts);
+    assert!(triangles == Err(earcutr::EarcutError::BadHoleIndex { hole: 0 }) && vertices == square);
+}
+
+#[test]
+fn test_minimize() {
+    let failing = |data: &Vec<f64>, holes: &Vec<usize>, dims| {
+        let triangles = earcutr::earcut(data, holes, dims);
+        earcutr::deviation(data, holes, dims, &triangles) > 1e-3
+    };
+    let rings = load_fixture("bad-hole");
+    let minimal = earcutr::minimize(&rings, failing).unwrap();
+    let (data, holes, dims) = earcutr::flatten(&minimal);
+    assert!(failing(&data, &holes, dims));
+    assert!(data.len() < 20 * dims);
+    // what is written out loads back the same, NaN included
+    let path = std::env::temp_dir().join("earcutr-test-minimize.json");
+    let nan = vec![vec![vec![f64::NAN, 1e300], vec![0.0, -0.5], vec![1.0, 5e-324]]];
+    for rings in &[minimal, nan] {
+        File::create(&path).unwrap().write_all(earcutr::fixture_json(rings).unwrap().as_bytes()).unwrap();
+        assert!(format!("{:?}", load_file(path.to_str().unwrap())) == format!("{:?}", rings));
+    }
+    std::fs::remove_file(&path).unwrap();
+    assert!(earcutr::minimize(&load_fixture("building"), failing).is_none());
+
+    // JSON has nothing for infinity
+    let rings = vec![vec![vec![0.0, 0.0], vec![1.0, f64::INFINITY]]];
+    assert!(earcutr::fixture_json(&rings) == Err(earcutr::EarcutError::OutOfRange { vertex: 1 }));
+}
+
+#[test]
+fn test_quality_report() {
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("water"));
+    let triangles = earcutr::earcut(&data, &holeidxs, dims);
+    let r = earcutr::quality_report(&data, dims, &triangles);
+    assert!(r.triangles.len() == triangles.len() / 3 && r.degenerate == 0);
+    assert!(r.min_angle > 0.0 && r.min_angle < r.mean_min_angle && r.mean_min_angle < 60.0);
+    assert!(r.max_angle < 180.0 && r.max_aspect_ratio >= r.mean_aspect_ratio && r.mean_aspect_ratio > 1.0);
+    assert!(r.min_area <= r.max_area && r.max_area <= r.total_area);
+    let area = earcutr::deviation_report(&data, &holeidxs, dims, &triangles).triangles_area;
+    assert!((r.total_area - area).abs() < 1e-9 * area);
+    assert!(r.min_angles.counts.iter().sum::<usize>() == r.triangles.len());
+    let mut edges: Vec<(usize, usize)> = triangles
+        .chunks(3)
+        .flat_map(|t| (0..3).map(move |i| (t[i].min(t[(i + 1) % 3]), t[i].max(t[(i + 1) % 3]))))
+        .collect();
+    edges.sort_unstable();
+    edges.dedup();
+    assert!(r.edge_lengths.counts.len() == 10 && r.edge_lengths.counts.iter().sum::<usize>() == edges.len());
+    // the delaunay pass gives rounder triangles
+    let opts = earcutr::EarcutOptions::new().delaunay(true);
+    let flipped = earcutr::earcut_with_options(&data, &holeidxs, dims, &opts).unwrap();
+    let f = earcutr::quality_report(&data, dims, &flipped);
+    assert!(f.min_angle > r.min_angle && f.mean_min_angle > r.mean_min_angle);
+    assert!(f.max_aspect_ratio < r.max_aspect_ratio);
+
+    // a polygon that crosses itself, where earcut makes a triangle of
+    // zero area, along y = 1
+    let data = vec![2.0, 1.0, 0.0, 0.0, 0.0, 0.0, 3.0, 1.0, 0.0, 1.0, 3.0, 2.0, 3.0, 0.0, 1.0, 0.0];
+    let triangles = earcutr::earcut(&data, &vec![], 2);
+    let r = earcutr::quality_report(&data, 2, &triangles);
+    assert!(r.degenerate == 1 && r.triangles.len() == 4 && r.min_angle > 0.0);
+    let opts = earcutr::EarcutOptions::new().drop_degenerate(true);
+    let (result, stats) = earcutr::earcut_with_stats(&data, &vec![], 2, &opts);
+    let result = result.unwrap();
+    assert!(stats.degenerate == 1 && result.len() == 9);
+    assert!(earcutr::quality_report(&data, 2, &result).degenerate == 0);
+    assert!(earcutr::deviation(&data, &vec![], 2, &result) == earcutr::deviation(&data, &vec![], 2, &triangles));
+    // nothing to measure
+    assert!(earcutr::quality_report(&data, 2, &[]) == earcutr::QualityReport {
+        min_angles: earcutr::Histogram { start: 0.0, width: 10.0, counts: vec![0; 6] },
+        ..Default::default()
+    });
+}
+
+#[test]
+fn test_deviation_report() {
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("water2"));
+    let triangles = earcutr::earcut(&data, &holeidxs, dims);
+    let r = earcutr::deviation_report(&data, &holeidxs, dims, &triangles);
+    assert!(r.rings.len() == holeidxs.len() + 1);
+    assert!(r.relative < 1e-12 && r.outside < 1e-12 * r.polygon_area);
+    assert!(r.rings.iter().all(|ring| ring.relative < 1e-9));
+
+    // the error deviation() reports is in rings that lose, or gain, area
+    let (data, holeidxs, dims) = earcutr::flatten(&load_fixture("water"));
+    let triangles = earcutr::earcut(&data, &holeidxs, dims);
+    let r = earcutr::deviation_report(&data, &holeidxs, dims, &triangles);
+    let deviation = earcutr::deviation(&data, &holeidxs, dims, &triangles);
+    assert!((r.relative - deviation).abs() < 1e-9);
+    assert!(r.absolute == (r.triangles_area - r.polygon_area).abs());
+    assert!(r.rings.iter().any(|ring| ring.relative > 1e-6));
+
+    let r = earcutr::deviation_report(&[0.0, 1.0, 2.0], &[], 1, &[0, 1, 2]);
+    assert!(r.triangles_area == 0.0 && r.rings.len() == 1 && r.rings[0].area == 0.0);
+}
+
+#[test]
+fn test_normalize() {
+    // scaled by 2^700, products of coordinates overflow unless normalized
+    let opts = earcutr::EarcutOptions::new().normalize(true);
+    for name in &["hole-touching-outer", "water3b"] {
+        let (data, holeidxs, dims) = earcutr::flatten(&load_fixture(name));
+        let huge: Vec<f64> = data.iter().map(|c| c * 2f64.powi(700)).collect();
+        let triangles = earcutr::earcut(&data, &holeidxs, dims);
+        let normalized = earcutr::earcut_with_options(&huge, &holeidxs, dims, &opts).unwrap();
+        assert!(normalized == triangles);
+        assert!(earcutr::earcut(&huge, &holeidxs, dims) != triangles);
+    }
+}
+
 #[test]
 fn test_building() {
     assert!(area_test("building", 13, 0e0));
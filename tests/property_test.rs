@@ -0,0 +1,356 @@
+// properties that hold for every polygon of a kind, checked over many
+// polygons from earcutr::generate. a failing case prints its seed; the
+// polygon is then earcutr::generate::<kind>(seed, ...), and
+// earcutr::minimize can shrink it further
+
+extern crate earcutr;
+
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
+
+use earcutr::generate::{self, Rng};
+
+type Rings = Vec<Vec<Vec<f64>>>;
+
+// how many polygons of each kind to try
+const CASES: u64 = 50;
+
+// simple polygons without holes, of a random size, for a seed
+fn simple(seed: u64) -> Vec<(&'static str, Rings)> {
+    let mut rng = Rng::new(seed);
+    vec![
+        ("star", generate::star(seed, 3 + rng.below(300))),
+        ("spiral", generate::spiral(seed, 4 + rng.below(300), rng.range(0.5, 4.0))),
+        ("comb", generate::comb(seed, 1 + rng.below(50))),
+        ("hilbert", generate::hilbert(1 + (seed % 4) as u32)),
+        ("sliver", generate::sliver(seed, 4 + rng.below(300), rng.range(1.0, 100.0))),
+    ]
+}
+
+// the simple polygons of a seed, and those of the kinds in 'more'
+fn kinds(seed: u64, more: &[&'static str]) -> Vec<(&'static str, Rings)> {
+    let mut kinds = simple(seed);
+    let n = seed as usize;
+    for &kind in more {
+        let rings = match kind {
+            "holes" => generate::holes(seed, 1 + n, 3 + n % 10),
+            "self-touching" => generate::self_touching(seed, 2 + n % 10),
+            "thin sliver" => generate::sliver(seed, 100, 1e-9),
+            _ => panic!("no polygons of kind {}", kind),
+        };
+        kinds.push((kind, rings));
+    }
+    kinds
+}
+
+// a polygon to check a property on, flattened, with earcut's triangles
+struct Case {
+    kind: &'static str,
+    seed: u64,
+    rings: Rings,
+    data: Vec<f64>,
+    holes: Vec<usize>,
+    triangles: Vec<usize>,
+}
+
+// check 'property' on kinds(seed, more) for each of 'cases' seeds. a
+// failure prints the kind and the seed
+fn check(cases: u64, more: &[&'static str], property: impl Fn(&Case)) {
+    for seed in 0..cases {
+        for (kind, rings) in kinds(seed, more) {
+            let (data, holes, triangles) = triangulate(&rings);
+            let case = Case { kind, seed, rings, data, holes, triangles };
+            if let Err(e) = catch_unwind(AssertUnwindSafe(|| property(&case))) {
+                eprintln!("failed on {} {}", kind, seed);
+                resume_unwind(e);
+            }
+        }
+    }
+}
+
+fn triangulate(rings: &Rings) -> (Vec<f64>, Vec<usize>, Vec<usize>) {
+    let (data, holes, dims) = earcutr::flatten(rings);
+    let triangles = earcutr::earcut(&data, &holes, dims);
+    (data, holes, triangles)
+}
+
+fn transform(rings: &Rings, f: &dyn Fn(&Vec<f64>) -> Vec<f64>) -> Rings {
+    rings.iter().map(|ring| ring.iter().map(f).collect()).collect()
+}
+
+// every ring started k points later; the vertex i of the original is
+// vertex map[i] of the rotated polygon
+fn rotate(rings: &Rings, k: usize) -> (Rings, Vec<usize>) {
+    let (mut rotated, mut map, mut start) = (Vec::new(), Vec::new(), 0);
+    for ring in rings {
+        let k = k % ring.len();
+        rotated.push([&ring[k..], &ring[..k]].concat());
+        map.extend((0..ring.len()).map(|i| start + (i + ring.len() - k) % ring.len()));
+        start += ring.len();
+    }
+    (rotated, map)
+}
+
+#[test]
+fn test_valid_indices() {
+    check(CASES, &["holes", "self-touching", "thin sliver"], |c| {
+        assert!(c.triangles.len().is_multiple_of(3));
+        assert!(c.triangles.iter().all(|&i| i < c.data.len() / 2));
+    });
+}
+
+#[test]
+fn test_simple_polygons() {
+    // n vertices and h holes that don't touch: n + 2h - 2 triangles
+    check(CASES, &["holes"], |c| {
+        assert!(c.triangles.len() / 3 == c.data.len() / 2 + 2 * c.holes.len() - 2);
+        assert!(earcutr::deviation(&c.data, &c.holes, 2, &c.triangles) < 1e-12);
+    });
+}
+
+#[test]
+fn test_translation_and_scaling() {
+    check(CASES, &["holes"], |c| {
+        let mut rng = Rng::new(c.seed);
+        // powers of two, and whole numbers on whole number coordinates,
+        // change no rounding, so the triangles are the very same
+        let s = 2f64.powi(rng.below(40) as i32 - 20);
+        let (dx, dy) = (rng.below(1 << 20) as f64, rng.below(1 << 20) as f64);
+        let scaled = transform(&c.rings, &|p| vec![p[0] * s, p[1] * s]);
+        assert!(triangulate(&scaled).2 == c.triangles, "scaled");
+        if c.kind == "comb" || c.kind == "hilbert" {
+            let moved = transform(&c.rings, &|p| vec![p[0] + dx, p[1] - dy]);
+            assert!(triangulate(&moved).2 == c.triangles, "moved");
+        }
+        // anywhere else, the triangles may differ, but not how many
+        // there are or the area they cover. far from 0,0, deviation()
+        // itself rounds more
+        let moved = transform(&c.rings, &|p| vec![p[0] + dx / 7.0, p[1] - dy / 3.0]);
+        let (data, holes, moved) = triangulate(&moved);
+        assert!(moved.len() == c.triangles.len(), "moved");
+        assert!(earcutr::deviation(&data, &holes, 2, &moved) < 1e-9, "moved");
+    });
+}
+
+#[test]
+fn test_ring_rotation() {
+    check(CASES, &["holes"], |c| {
+        let (rotated, map) = rotate(&c.rings, 1 + c.seed as usize);
+        let (data, holes, rotated) = triangulate(&rotated);
+        // other ears get clipped first, but the triangles are as many,
+        // cover the polygon, and use the same vertices
+        assert!(rotated.len() == c.triangles.len());
+        assert!(earcutr::deviation(&data, &holes, 2, &rotated) < 1e-12);
+        let mut used: Vec<usize> = c.triangles.iter().map(|&i| map[i]).collect();
+        let mut used_rotated = rotated.clone();
+        for v in [&mut used, &mut used_rotated] {
+            v.sort_unstable();
+            v.dedup();
+        }
+        assert!(used == used_rotated);
+    });
+}
+
+#[test]
+fn test_delaunay() {
+    let opts = earcutr::EarcutOptions::new().delaunay(true);
+    check(CASES, &["holes"], |c| {
+        let flipped = earcutr::earcut_with_options(&c.data, &c.holes, 2, &opts).unwrap();
+        assert!(flipped.len() == c.triangles.len());
+        // a spiral of few vertices per turn cuts across itself, and its
+        // triangles overlap either way
+        let valid = earcutr::verify(&c.data, &c.holes, 2, &c.triangles).is_valid();
+        assert!(earcutr::verify(&c.data, &c.holes, 2, &flipped).is_valid() == valid);
+    });
+}
+
+#[test]
+fn test_best_ears() {
+    let opts = earcutr::EarcutOptions::new().best_ears(true);
+    check(CASES, &["holes"], |c| {
+        let best = earcutr::earcut_with_options(&c.data, &c.holes, 2, &opts).unwrap();
+        assert!(best.len() == c.triangles.len());
+        if earcutr::verify(&c.data, &c.holes, 2, &c.triangles).is_valid() {
+            assert!(earcutr::verify(&c.data, &c.holes, 2, &best).is_valid());
+        }
+    });
+}
+
+#[test]
+fn test_optimal() {
+    // small enough for the unhashed path
+    let objectives = [earcutr::Objective::MaxMinAngle, earcutr::Objective::MinMaxAngle, earcutr::Objective::MinWeight];
+    for seed in 0..CASES {
+        let n = 3 + seed as usize % 30;
+        let kinds = vec![
+            ("star", generate::star(seed, n)),
+            ("comb", generate::comb(seed, 1 + n / 4)),
+            ("sliver", generate::sliver(seed, n, 10.0)),
+        ];
+        for (kind, rings) in kinds {
+            let (data, holes, triangles) = triangulate(&rings);
+            let opts = earcutr::EarcutOptions::new().optimal(objectives[seed as usize % 3]);
+            let (result, stats) = earcutr::earcut_with_stats(&data, &holes, 2, &opts);
+            let result = result.unwrap();
+            assert!(stats.optimal == 1 && result.len() == triangles.len(), "{} {}", kind, seed);
+            assert!(earcutr::verify(&data, &holes, 2, &result).is_valid(), "{} {}", kind, seed);
+        }
+    }
+}
+
+#[test]
+fn test_drop_degenerate() {
+    let opts = earcutr::EarcutOptions::new().drop_degenerate(true);
+    check(CASES, &["self-touching"], |c| {
+        let report = earcutr::quality_report(&c.data, 2, &c.triangles);
+        assert!(report.triangles.len() == c.triangles.len() / 3);
+        let (result, stats) = earcutr::earcut_with_stats(&c.data, &c.holes, 2, &opts);
+        let result = result.unwrap();
+        assert!(stats.degenerate == report.degenerate);
+        assert!(result.len() + 3 * stats.degenerate == c.triangles.len());
+        assert!(earcutr::quality_report(&c.data, 2, &result).degenerate == 0);
+    });
+}
+
+#[test]
+fn test_refinement() {
+    // refined, the triangles still cover the polygon, and stay
+    // counterclockwise. verify would find slivers of gap and overlap:
+    // new vertices on slanted ring edges are rounded off them
+    let opts = earcutr::EarcutOptions::new();
+    let refinement = earcutr::Refinement::new().min_angle(20.0);
+    // fewer polygons than elsewhere, refined they have about ten times
+    // the vertices
+    check(CASES / 5, &["holes"], |c| {
+        let (refined, vertices) = earcutr::earcut_with_refinement(&c.data, &c.holes, 2, &refinement, &opts);
+        let refined = refined.unwrap();
+        let mut all_holes = c.holes.clone();
+        all_holes.extend(c.data.len() / 2..vertices.len() / 2);
+        let deviation = earcutr::deviation(&c.data, &c.holes, 2, &c.triangles);
+        let refined_deviation = earcutr::deviation(&vertices, &all_holes, 2, &refined);
+        assert!((refined_deviation - deviation).abs() < 1e-12);
+        let area = |t: &[usize]| {
+            let p = |i: usize| [vertices[2 * t[i]], vertices[2 * t[i] + 1]];
+            let ([ax, ay], [bx, by], [cx, cy]) = (p(0), p(1), p(2));
+            (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
+        };
+        assert!(refined.chunks(3).all(|t| area(t) > 0.0));
+    });
+}
+
+#[test]
+fn test_no_panic() {
+    // arbitrary coordinates, NaN and infinity among them, in up to four
+    // rings of any size, with hole indices in any order and range, and
+    // maybe a coordinate too many
+    for seed in 0..2000 {
+        let mut rng = Rng::new(seed);
+        let n = rng.below(20);
+        let data: Vec<f64> = (0..2 * n + rng.below(2))
+            .map(|_| match rng.below(12) {
+                0 => f64::NAN,
+                1 => f64::INFINITY,
+                2 => -f64::INFINITY,
+                3 => rng.range(-1e300, 1e300),
+                _ => rng.below(8) as f64,
+            })
+            .collect();
+        let holes: Vec<usize> = (0..rng.below(4)).map(|_| rng.below(n + 3)).collect();
+        let triangles = earcutr::earcut(&data, &holes, 2);
+        assert!(triangles.len().is_multiple_of(3) && triangles.iter().all(|&i| i < n), "{}", seed);
+
+        let rings: Vec<Vec<Vec<f64>>> = (0..rng.below(4))
+            .map(|_| (0..rng.below(5)).map(|i| data[i.min(data.len())..].iter().take(2).cloned().collect()).collect())
+            .collect();
+        let (data, holes, dims) = earcutr::flatten(&rings);
+        let _ = earcutr::earcut(&data, &holes, dims);
+
+        // integers up to the ends of their type, which either triangulate
+        // or are out of range
+        let ints: Vec<i64> = (0..2 * n)
+            .map(|_| match rng.below(12) {
+                0 => i64::MIN,
+                1 => i64::MAX,
+                2 => 1 << 52,
+                3 => -(1 << 52),
+                4 => i32::MIN as i64,
+                5 => i32::MAX as i64,
+                _ => rng.below(8) as i64,
+            })
+            .collect();
+        let holes: Vec<usize> = (0..rng.below(3)).map(|_| rng.below(n + 1)).collect();
+        let opts = earcutr::EarcutOptions::new();
+        if let Ok(triangles) = earcutr::earcut_with_options(&ints, &holes, 2, &opts) {
+            assert!(triangles.len().is_multiple_of(3) && triangles.iter().all(|&i| i < n), "{}", seed);
+        }
+        let ints: Vec<i32> = ints.iter().map(|&c| c.clamp(i32::MIN as i64, i32::MAX as i64) as i32).collect();
+        if let Ok(triangles) = earcutr::earcut_with_options(&ints, &holes, 2, &opts) {
+            assert!(triangles.len().is_multiple_of(3) && triangles.iter().all(|&i| i < n), "{}", seed);
+        }
+    }
+}
+
+#[test]
+fn test_constraints() {
+    // polylines of whole number points anywhere in the bounding box. those
+    // that are kept have all their points in the triangles, and each of
+    // their segments is an edge of one triangle each way, also after the
+    // delaunay pass
+    for seed in 0..CASES {
+        let opts = earcutr::EarcutOptions::new().delaunay(seed % 2 == 1);
+        let mut rng = Rng::new(seed);
+        let rings = match seed % 3 {
+            0 => generate::star(seed, 3 + rng.below(300)),
+            1 => generate::holes(seed, 1 + rng.below(5), 3 + rng.below(8)),
+            _ => generate::comb(seed, 1 + rng.below(6)),
+        };
+        let (data, holes, dims) = earcutr::flatten(&rings);
+        let (lo, hi) = data.chunks(2).fold(([f64::MAX; 2], [f64::MIN; 2]), |(lo, hi), p| {
+            ([lo[0].min(p[0]), lo[1].min(p[1])], [hi[0].max(p[0]), hi[1].max(p[1])])
+        });
+        // some start at a vertex of the polygon
+        let polylines: Vec<Vec<f64>> = (0..1 + rng.below(20))
+            .map(|_| {
+                let mut line: Vec<f64> = (0..1 + rng.below(8))
+                    .flat_map(|_| vec![rng.range(lo[0], hi[0]).round(), rng.range(lo[1], hi[1]).round()])
+                    .collect();
+                if rng.below(3) == 0 {
+                    let v = rng.below(data.len() / 2);
+                    line.splice(..2, data[2 * v..2 * v + 2].iter().cloned());
+                }
+                line
+            })
+            .collect();
+        let (triangles, rejected) = earcutr::earcut_with_constraints(&data, &holes, dims, &polylines, &opts);
+        let triangles = triangles.unwrap();
+
+        // with the points as holes of one vertex, the triangles cover the
+        // polygon. a point at a vertex is that vertex
+        let mut all = data.clone();
+        let mut all_holes = holes.clone();
+        let mut number = Vec::new();
+        for p in polylines.iter().flat_map(|line| line.chunks(2)) {
+            number.push(data.chunks(2).position(|q| q == p).unwrap_or(all.len() / 2));
+            all_holes.push(all.len() / 2);
+            all.extend(p);
+        }
+        assert!(earcutr::deviation(&all, &all_holes, 2, &triangles) < 1e-12, "{}", seed);
+        let ends_on_rings = number.iter().enumerate().any(|(i, &v)| v != data.len() / 2 + i);
+        if !ends_on_rings {
+            assert!(earcutr::verify(&all, &all_holes, 2, &triangles).is_valid(), "{}", seed);
+        }
+
+        let edges = |a, b| triangles.chunks(3).filter(|t| (0..3).any(|e| t[e] == a && t[(e + 1) % 3] == b)).count();
+        let mut first = 0;
+        for (c, line) in polylines.iter().enumerate() {
+            let k = line.len() / 2;
+            if rejected.iter().all(|&(r, _)| r != c) {
+                let number = &number[first..first + k];
+                assert!(number.iter().all(|i| triangles.contains(i)), "{} {}", seed, c);
+                for s in number.windows(2) {
+                    assert!(edges(s[0], s[1]) == 1 && edges(s[1], s[0]) == 1, "{} {}", seed, c);
+                }
+            }
+            first += k;
+        }
+    }
+}
@@ -2,6 +2,7 @@ var currentTestName = "";
 var labelsToggle = 0;
 var fillToggle = 1;
 var labelmap = [];
+var traceStep = -1; // -1 shows the finished triangulation, not the trace
 
 function setupCanvas(viewerid,canvasid) {
 	// size must be reset because CSS sizing doesn't work on canvases.
@@ -125,6 +126,7 @@ function makeTriPset( mainPset, tris, mapr ) {
 }
 
 function drawPset( pset, canvas, mapr, labels, lastcontour, fillLevel, special ) {
+	if (pset.length==0) return; // no triangles yet, when replaying a trace
 	ctx = canvas.getContext('2d');
 	labelmap = [];
 	todraw = Math.min(pset.length,lastcontour);
@@ -175,6 +177,7 @@ function drawPset( pset, canvas, mapr, labels, lastcontour, fillLevel, special )
 function mkfunc( testname, canvasid ) {
     return function() {
 		currentTestName = testname;
+		traceStep = -1;
 		redraw( canvasid );
     }
 };
@@ -219,8 +222,74 @@ function drawTest( pset, tris, report, canvas, showlabels, fillLevel, special )
 	reportbox.innerHTML = report + '<br/>' + tris;
 }
 
+/* replay of the event trace written by the tests when they are run with
+   'cargo test --features trace'. the triangles clipped up to the current
+   step are drawn, and the current event is highlighted on top of them */
+function traceTriangles( trace, step ) {
+	var tris = [];
+	for (var i = 0; i <= step; i++) {
+		var ev = trace[i];
+		if (ev.event=="ear" || ev.event=="cure") tris.push(ev.a,ev.b,ev.c);
+	}
+	return tris;
+}
+
+function drawEvent( pset, ev, canvas ) {
+	var ctx = canvas.getContext('2d');
+	var mapr = new PointMapper( pset, canvas );
+	var vertices = flatten( pset );
+	var dim = pset[0][0].length;
+	var pt = function(i) { return [ mapr.x(vertices[i*dim]), mapr.y(vertices[i*dim+1]) ]; };
+	var path = function( idxs, closed, color ) {
+		if (idxs.length==0) return;
+		ctx.beginPath();
+		ctx.moveTo( pt(idxs[0])[0], pt(idxs[0])[1] );
+		for (var i = 1; i < idxs.length; i++) ctx.lineTo( pt(idxs[i])[0], pt(idxs[i])[1] );
+		if (closed) ctx.closePath();
+		ctx.strokeStyle = color;
+		ctx.lineWidth = 3;
+		ctx.stroke();
+		ctx.lineWidth = 1;
+	};
+	var dot = function( i, color ) {
+		ctx.beginPath();
+		ctx.arc( pt(i)[0], pt(i)[1], 6, 0, 2*Math.PI );
+		ctx.fillStyle = color;
+		ctx.fill();
+	};
+	switch (ev.event) {
+		case "ring": path( ev.ring, true, ev.hole ? "blue" : "green" ); break;
+		case "pass": path( ev.ring, true, "orange" ); break;
+		case "zorder": path( ev.order, false, "purple" ); break;
+		case "bridge": path( [ev.hole, ev.outer], false, "blue" ); break;
+		case "split": path( [ev.a, ev.b], false, "red" ); break;
+		case "ear":
+		case "cure": path( [ev.a, ev.b, ev.c], true, "green" ); break;
+		case "notear":
+			path( [ev.a, ev.b, ev.c], true, "red" );
+			if (ev.blocker!=null) dot( ev.blocker, "red" );
+			break;
+	}
+}
+
+function redrawTrace( canvasid ) {
+	var canvas = document.getElementById( "mycan" );
+	var trace = testOutput[currentTestName]["trace"];
+	var pset = testOutput[currentTestName]["json"];
+	var ev = trace[traceStep];
+	var rpttxt = currentTestName + '\nstep ' + traceStep + ' of ' + trace.length
+		+ '\n' + JSON.stringify(ev);
+	drawTest( pset, traceTriangles( trace, traceStep ), rpttxt, canvas,
+			  labelsToggle, fillToggle );
+	drawEvent( pset, ev, canvas );
+}
+
 function redraw( canvasid ) {
 	canvas = document.getElementById( "mycan" );
+	if (traceStep >= 0 && testOutput[currentTestName]["trace"]) {
+		redrawTrace( canvasid );
+		return;
+	}
 	rpttxt = currentTestName + '\n'+  testOutput[currentTestName]["report"];
 	drawTest( testOutput[currentTestName]["json"],
 			  testOutput[currentTestName]["triangles"],
@@ -253,6 +322,16 @@ function redrawEarcutVersion ( canvasid ) {
 function labelsfunc( canvasid ) { labelsToggle = (labelsToggle+1)%3;redraw(canvasid);};
 function trisfunc( canvasid ) { fillToggle = (fillToggle+1)%3; redraw(canvasid); };
 function earcutfunc( canvasid ) { redrawEarcutVersion( canvasid ); }
+// step through the trace; stepping back past the start, or forward past
+// the end, returns to the finished triangulation
+function stepfunc( delta ) {
+	var trace = testOutput[currentTestName]["trace"];
+	if (!trace) return;
+	traceStep += delta;
+	if (traceStep < -1) traceStep = trace.length - 1;
+	if (traceStep >= trace.length) traceStep = -1;
+	redraw( "mycan" );
+}
 
 function setupControls( canvasid ) {
     document.getElementById("labelsbutton").addEventListener('click',
@@ -261,6 +340,10 @@ function setupControls( canvasid ) {
 		trisfunc, canvasid );
     document.getElementById("earcutbutton").addEventListener('click', 
 		earcutfunc, canvasid );
+    document.getElementById("stepbackbutton").addEventListener('click',
+		function() { stepfunc(-1); } );
+    document.getElementById("stepbutton").addEventListener('click',
+		function() { stepfunc(1); } );
 }
 
 function main() {